@@ -0,0 +1,281 @@
+//! Implements baselines for `tire lint` and `tire check`.
+//!
+//! A baseline records the diagnostics that existed when it was created, so that Tire can be
+//! adopted in a codebase with many existing problems and only fail on new ones. Diagnostics are
+//! identified by their file, code and a fingerprint of the content of the affected line, rather
+//! than the line number, so that the baseline survives unrelated changes that shift lines.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use toml::Value;
+
+use crate::{
+    diagnostic::{Diagnostic, OutputFormat, Severity, render},
+    profile::Materialized,
+    utils::{Outcome, normalize_path, sha256_hex},
+};
+
+/// The default path of the baseline file, relative to the project root.
+const DEFAULT_BASELINE_FILE: &str = "tire-baseline.json";
+
+/// Identifies diagnostics that are considered the same across runs.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Key {
+    /// The tool that reported the diagnostic.
+    pub tool: String,
+
+    /// The path of the file relative to the project root, with forward slashes.
+    pub file: String,
+
+    /// The rule or error code.
+    pub code: String,
+
+    /// A hash of the content of the affected line, ignoring surrounding whitespace.
+    pub fingerprint: String,
+}
+
+/// An entry in the baseline file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(flatten)]
+    pub key: Key,
+
+    /// The number of diagnostics with this key.
+    pub count: usize,
+}
+
+/// The contents of a baseline file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub entries: Vec<Entry>,
+}
+
+/// The result of comparing diagnostics against a [Baseline].
+#[derive(Debug, Default)]
+pub struct Comparison {
+    /// Diagnostics that are not in the baseline.
+    pub new: Vec<Diagnostic>,
+
+    /// The number of diagnostics that were suppressed by the baseline.
+    pub suppressed: usize,
+
+    /// Baseline entries without matching diagnostics, with the number of diagnostics that are
+    /// gone.
+    pub fixed: Vec<Entry>,
+}
+
+impl Baseline {
+    /// Load the baseline from the given file, or return an empty baseline if it does not exist.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write the baseline to the given file.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// Replace the entries of the given tool in the *checked* paths (see [checked_paths]) with the
+    /// given keys.
+    pub fn update(&mut self, tool: &str, keys: &[Key], checked: Option<&[String]>) {
+        self.entries
+            .retain(|x| x.key.tool != tool || !is_checked(&x.key.file, checked));
+        for (key, count) in count_keys(keys) {
+            self.entries.push(Entry { key, count });
+        }
+        self.entries.sort_by(|a, b| a.key.cmp(&b.key));
+    }
+
+    /// Compare the diagnostics of the given tool against the baseline. *keys* must contain the
+    /// key of each diagnostic at the same index. Only entries in the *checked* paths (see
+    /// [checked_paths]) can be reported as fixed.
+    pub fn compare(
+        &self,
+        tool: &str,
+        diagnostics: Vec<Diagnostic>,
+        keys: &[Key],
+        checked: Option<&[String]>,
+    ) -> Comparison {
+        let mut allowed: HashMap<&Key, usize> = self
+            .entries
+            .iter()
+            .filter(|x| x.key.tool == tool && is_checked(&x.key.file, checked))
+            .map(|x| (&x.key, x.count))
+            .collect();
+
+        let mut comparison = Comparison::default();
+        for (diagnostic, key) in diagnostics.into_iter().zip(keys) {
+            match allowed.get_mut(key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    comparison.suppressed += 1;
+                }
+                _ => comparison.new.push(diagnostic),
+            }
+        }
+
+        comparison.fixed = allowed
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(key, count)| Entry {
+                key: key.clone(),
+                count,
+            })
+            .collect();
+        comparison.fixed.sort_by(|a, b| a.key.cmp(&b.key));
+        comparison
+    }
+}
+
+/// Returns the paths of the *files* (and directories) passed to a tool, relative to the project
+/// root with forward slashes, or [None] if the tool checked the whole project. Paths outside the
+/// project are left out.
+pub fn checked_paths(project_root: &Path, cwd: &Path, files: &[String]) -> Option<Vec<String>> {
+    let mut checked = Vec::new();
+    for file in files {
+        let path = normalize_path(&cwd.join(file));
+        if let Ok(relative) = path.strip_prefix(project_root) {
+            if relative.as_os_str().is_empty() {
+                return None;
+            }
+            checked.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    (!files.is_empty()).then_some(checked)
+}
+
+/// Checks if the *file* of a baseline entry is one of the *checked* paths or in one of them.
+fn is_checked(file: &str, checked: Option<&[String]>) -> bool {
+    checked.is_none_or(|paths| {
+        paths
+            .iter()
+            .any(|x| Path::new(file).starts_with(Path::new(x)))
+    })
+}
+
+/// Count the occurrences of each key.
+fn count_keys(keys: &[Key]) -> BTreeMap<Key, usize> {
+    let mut counts = BTreeMap::new();
+    for key in keys {
+        *counts.entry(key.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Computes the baseline keys of the given diagnostics, reading the affected lines from disk.
+pub fn keys(project_root: &Path, diagnostics: &[Diagnostic]) -> Vec<Key> {
+    let cwd = std::env::current_dir().unwrap();
+    let mut files: HashMap<&str, Vec<String>> = HashMap::new();
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let lines = files.entry(&diagnostic.file).or_insert_with(|| {
+                std::fs::read_to_string(cwd.join(&diagnostic.file))
+                    .map(|x| x.lines().map(String::from).collect())
+                    .unwrap_or_default()
+            });
+            let content = lines
+                .get(diagnostic.line.saturating_sub(1))
+                .map(String::as_str)
+                .unwrap_or_default();
+            let path = cwd.join(&diagnostic.file);
+            let file = path.strip_prefix(project_root).unwrap_or(&path);
+            key(diagnostic, &file.to_string_lossy(), content)
+        })
+        .collect()
+}
+
+/// Computes the baseline key of a diagnostic in *file* (relative to the project root), given the
+/// *content* of the affected line.
+pub fn key(diagnostic: &Diagnostic, file: &str, content: &str) -> Key {
+    Key {
+        tool: diagnostic.tool.clone(),
+        file: file.replace('\\', "/"),
+        code: diagnostic.code_or_unknown().to_owned(),
+        fingerprint: sha256_hex(content.trim())[..16].to_owned(),
+    }
+}
+
+/// Returns the path of the baseline file, configured with `[tool.tire].baseline`.
+pub fn baseline_file(materialized: &Materialized) -> PathBuf {
+    let file = materialized
+        .setting(&["baseline"])
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_BASELINE_FILE);
+    materialized.project_root.join(file)
+}
+
+/// Report the diagnostics of *tool* in the given format, taking the baseline into account. If
+/// *update* is set, the baseline is updated with the diagnostics instead. *files* are the files
+/// and directories passed to the tool, which limit the affected baseline entries on partial runs,
+/// or empty if the whole project was checked.
+pub fn report(
+    tool: &str,
+    diagnostics: Vec<Diagnostic>,
+    files: &[String],
+    format: OutputFormat,
+    update: bool,
+    materialized: &Materialized,
+) -> Outcome {
+    let path = baseline_file(materialized);
+    let mut baseline = match Baseline::load(&path) {
+        Ok(baseline) => baseline,
+        Err(err) => {
+            eprintln!("[tire] Failed to load baseline {}: {err}", path.display());
+            return Outcome::Error;
+        }
+    };
+    let keys = keys(&materialized.project_root, &diagnostics);
+    let cwd = std::env::current_dir().unwrap();
+    let checked = checked_paths(&materialized.project_root, &cwd, files);
+
+    if update {
+        baseline.update(tool, &keys, checked.as_deref());
+        if let Err(err) = baseline.save(&path) {
+            eprintln!("[tire] Failed to write baseline {}: {err}", path.display());
+            return Outcome::Error;
+        }
+        eprintln!(
+            "[tire] Recorded {} `{tool}` diagnostic(s) in {}",
+            diagnostics.len(),
+            path.display()
+        );
+        return Outcome::Success;
+    }
+
+    let comparison = baseline.compare(tool, diagnostics, &keys, checked.as_deref());
+    print!("{}", render(format, &comparison.new));
+
+    if comparison.suppressed > 0 {
+        eprintln!(
+            "[tire] {} `{tool}` diagnostic(s) suppressed by the baseline",
+            comparison.suppressed
+        );
+    }
+    if !comparison.fixed.is_empty() {
+        eprintln!(
+            "[tire] Baseline entries that are now fixed (run with `--update-baseline` to remove them):"
+        );
+        for entry in &comparison.fixed {
+            eprintln!(
+                "[tire]   {} {} ({}x)",
+                entry.key.file, entry.key.code, entry.count
+            );
+        }
+    }
+
+    // Notes only add context to other diagnostics and don't fail the run by themselves.
+    if comparison.new.iter().all(|x| x.severity == Severity::Note) {
+        Outcome::Success
+    } else {
+        Outcome::Failure
+    }
+}
@@ -0,0 +1,207 @@
+//! Implements caching the results of `tire lint` and `tire check`.
+//!
+//! When a tool finds no problems, Tire records a key that identifies everything the result depends
+//! on: the tool and its version, the materialized configuration, the files the tool was run on,
+//! and the content of all Python files in the project (type-checking a file depends on the files
+//! it imports), the lock file and the baseline. If the key of a later run is in the cache, the tool
+//! isn't invoked at all.
+//!
+//! Only successful runs are cached, so problems are always reported by the tools themselves.
+
+use std::path::{Component, Path, PathBuf};
+
+use toml::Value;
+
+use crate::{
+    baseline::baseline_file,
+    diagnostic::{OutputFormat, render},
+    profile::Materialized,
+    select::is_python_file,
+    utils::{Outcome, command_output, sha256_hex},
+};
+
+/// The environment variable that overrides the cache directory.
+pub const CACHE_DIR_ENV: &str = "TIRE_CACHE_DIR";
+
+/// The maximum number of entries to keep in the cache directory.
+const MAX_ENTRIES: usize = 256;
+
+/// Returns the cache directory, configured with the [CACHE_DIR_ENV] environment variable or the
+/// `[tool.tire].cache-dir` setting, both relative to the project root. Defaults to `cache/` in the
+/// state directory.
+pub fn cache_dir(materialized: &Materialized) -> PathBuf {
+    if let Ok(dir) = std::env::var(CACHE_DIR_ENV) {
+        if !dir.is_empty() {
+            return materialized.project_root.join(dir);
+        }
+    }
+    match materialized.setting(&["cache-dir"]).and_then(Value::as_str) {
+        Some(dir) => materialized.project_root.join(dir),
+        None => materialized.state_dir().join("cache"),
+    }
+}
+
+/// Computes the cache key of a tool run from everything that its result depends on. *files* are
+/// pairs of paths and content hashes.
+pub fn run_key(
+    tool: &str,
+    version: &str,
+    config_hash: &str,
+    args: &[String],
+    files: &[(String, String)],
+) -> String {
+    let mut input = format!("{tool}\0{version}\0{config_hash}\0");
+    for arg in args {
+        input += &format!("arg\0{arg}\0");
+    }
+    for (path, hash) in files {
+        input += &format!("file\0{path}\0{hash}\0");
+    }
+    sha256_hex(input)
+}
+
+/// Resolves the file arguments of a tool run, relative to *cwd*, to paths relative to the project
+/// root, so that a run in a subdirectory doesn't share its key with a run of the same arguments in
+/// the project root. No arguments refer to *cwd*. Paths outside the project stay absolute.
+pub fn project_args(project_root: &Path, cwd: &Path, args: &[String]) -> Vec<String> {
+    let args = if args.is_empty() {
+        &[".".to_owned()][..]
+    } else {
+        args
+    };
+    args.iter()
+        .map(|arg| {
+            let mut path = PathBuf::new();
+            for component in cwd.join(arg).components() {
+                match component {
+                    Component::CurDir => {}
+                    Component::ParentDir => {
+                        path.pop();
+                    }
+                    other => path.push(other),
+                }
+            }
+            match path.strip_prefix(project_root) {
+                Ok(relative) if relative.as_os_str().is_empty() => ".".to_owned(),
+                Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+                Err(_) => path.to_string_lossy().to_string(),
+            }
+        })
+        .collect()
+}
+
+/// A cache of successful runs of a tool in the project of a [Materialized] configuration.
+pub struct Cache {
+    tool: String,
+    version: String,
+    dir: PathBuf,
+    project_root: PathBuf,
+    config_hash: String,
+    baseline_file: PathBuf,
+}
+
+impl Cache {
+    /// Returns the cache for *tool*, or [None] if the version of the tool can't be determined, in
+    /// which case its results can't be cached.
+    pub fn new(materialized: &Materialized, tool: &str) -> Option<Self> {
+        let version = command_output(
+            "uv",
+            &["run", "--with", tool, tool, "--version"],
+            &materialized.project_root,
+        )?;
+        Some(Self {
+            tool: tool.to_owned(),
+            version,
+            dir: cache_dir(materialized),
+            project_root: materialized.project_root.clone(),
+            config_hash: sha256_hex(std::fs::read(&materialized.file).unwrap_or_default()),
+            baseline_file: baseline_file(materialized),
+        })
+    }
+
+    /// Returns the paths and content hashes of the files that tool results depend on, relative to
+    /// the project root and sorted by path.
+    fn files(&self) -> Vec<(String, String)> {
+        // `.gitignore` files apply even if the project is not a Git repository, so that e.g. a
+        // virtual environment in `venv/` is not hashed.
+        let mut paths: Vec<PathBuf> = ignore::WalkBuilder::new(&self.project_root)
+            .require_git(false)
+            .build()
+            .filter_map(Result::ok)
+            .map(|x| x.into_path())
+            .filter(|x| x.is_file() && is_python_file(x))
+            .collect();
+        paths.push(self.project_root.join("uv.lock"));
+        paths.push(self.baseline_file.clone());
+
+        let mut files: Vec<(String, String)> = paths
+            .iter()
+            .filter_map(|path| {
+                let content = std::fs::read(path).ok()?;
+                let relative = path.strip_prefix(&self.project_root).unwrap_or(path);
+                Some((relative.to_string_lossy().to_string(), sha256_hex(content)))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Computes the cache key for running the tool with the given file arguments, relative to the
+    /// current working directory, based on the current content of the project.
+    pub fn key(&self, args: &[String]) -> String {
+        let cwd = std::env::current_dir().unwrap();
+        run_key(
+            &self.tool,
+            &self.version,
+            &self.config_hash,
+            &project_args(&self.project_root, &cwd, args),
+            &self.files(),
+        )
+    }
+
+    /// Checks if a successful run with the given key was recorded.
+    pub fn contains(&self, key: &str) -> bool {
+        self.dir.join(key).exists()
+    }
+
+    /// Records a successful run with the given key.
+    pub fn insert(&self, key: &str) {
+        let result =
+            std::fs::create_dir_all(&self.dir).and_then(|_| std::fs::write(self.dir.join(key), ""));
+        if let Err(err) = result {
+            log::warn!("Failed to write to cache {}: {err}", self.dir.display());
+            return;
+        }
+        self.prune();
+    }
+
+    /// Removes the oldest entries if there are more than [MAX_ENTRIES].
+    fn prune(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut entries: Vec<(std::time::SystemTime, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .filter_map(|x| Some((x.metadata().ok()?.modified().ok()?, x.path())))
+            .collect();
+        if entries.len() <= MAX_ENTRIES {
+            return;
+        }
+        entries.sort();
+        for (_, path) in &entries[..entries.len() - MAX_ENTRIES] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Report a skipped run of *tool* whose result was cached. Renders an empty report for formats
+/// other than [OutputFormat::Text], so that CI systems still find one.
+pub fn report_cached(tool: &str, format: OutputFormat) -> Outcome {
+    eprintln!(
+        "[tire] Nothing changed since `{tool}` last succeeded, skipping it (use `--no-cache` to run it anyway)."
+    );
+    if format != OutputFormat::Text {
+        print!("{}", render(format, &[]));
+    }
+    Outcome::Success
+}
@@ -1,25 +1,42 @@
 //! Implements the `tire check` command.
 
+use std::path::{Path, PathBuf};
+
 use crate::{
-    profile::Profile,
-    utils::{run_command_or_exit, string_vec},
+    baseline::{self, baseline_file},
+    cache::{Cache, report_cached},
+    diagnostic::{OutputFormat, parse_mypy_output},
+    profile::{Materialized, Profile, state_dir},
+    utils::{
+        ExitCodes, Outcome, capture_command, find_project_root, run_command, run_tool, sha256_hex,
+        string_vec, write_if_changed,
+    },
 };
 
-pub fn check(files: Vec<String>) {
-    // Load the project's pyproject.toml
-    let pyproject_toml = Profile::load(None).unwrap().materialize(None).unwrap();
-
-    // The dmypy status file should sit next to the pyproject.toml, to reuse the same daemon
-    // for the same project even if run in a subdirectory.
-    // TODO: Use the root `pyproject.toml` for a Uv workspace project if the current project is
-    //       a workspace member.
-    let status_file = pyproject_toml
-        .parent()
-        .unwrap()
-        .join(".dmypy.json")
-        .to_path_buf();
-
-    // Run dmypy with the merged config file
+/// Returns the path to the dmypy status file of the project.
+///
+/// The dmypy status file sits in the project's state directory, to reuse the same daemon for the
+/// same project even if run in a subdirectory, and to keep it out of version control.
+///
+/// TODO: Use the root `pyproject.toml` for a Uv workspace project if the current project is
+///       a workspace member.
+pub fn status_file(project_root: &Path) -> PathBuf {
+    state_dir(project_root).join("dmypy.json")
+}
+
+/// Returns the path where older versions of Tire placed the dmypy status file.
+pub fn legacy_status_file(project_root: &Path) -> PathBuf {
+    project_root.join(".dmypy.json")
+}
+
+/// Returns the file in which we remember the hash of the configuration that the daemon was
+/// started with.
+pub fn config_hash_file(project_root: &Path) -> PathBuf {
+    state_dir(project_root).join("dmypy.sha256")
+}
+
+/// Build a `dmypy` command for the daemon identified by the given status file.
+fn dmypy_command(status_file: &Path, args: &[&str]) -> Vec<String> {
     let mut uv_command = string_vec![
         "uv",
         "run",
@@ -27,18 +44,154 @@ pub fn check(files: Vec<String>) {
         "mypy",
         "dmypy",
         "--status-file",
-        status_file.to_string_lossy(),
-        "run",
-        "--",
-        "--config-file",
-        pyproject_toml.to_string_lossy().to_string()
+        status_file.to_string_lossy()
     ];
+    uv_command.extend(args.iter().map(|x| x.to_string()));
+    uv_command
+}
+
+/// Stop the dmypy daemon of the project, if there is one. Returns `true` if a daemon was stopped.
+pub fn stop_daemon(project_root: &Path) -> bool {
+    let status_file = status_file(project_root);
+    if !status_file.exists() {
+        return false;
+    }
+    let _ = std::fs::remove_file(config_hash_file(project_root));
+    run_command(dmypy_command(&status_file, &["stop"])).success()
+}
+
+/// Stop a dmypy daemon started by an older version of Tire, which placed the status file in the
+/// project root, and remove the status file.
+pub fn stop_legacy_daemon(project_root: &Path) {
+    let status_file = legacy_status_file(project_root);
+    if !status_file.exists() {
+        return;
+    }
+    eprintln!("[tire] Stopping dmypy daemon of an older version of Tire.");
+    run_command(dmypy_command(&status_file, &["stop"]));
+    let _ = std::fs::remove_file(status_file);
+}
+
+pub fn check(
+    files: Vec<String>,
+    status: bool,
+    stop: bool,
+    restart: bool,
+    format: OutputFormat,
+    update_baseline: bool,
+    no_cache: bool,
+) -> Outcome {
+    let project_root = find_project_root(None).unwrap();
+    let status_file = status_file(&project_root);
+    stop_legacy_daemon(&project_root);
+
+    if status || stop {
+        if !status_file.exists() {
+            eprintln!("[tire] No dmypy daemon is running.");
+            return if status {
+                Outcome::Failure
+            } else {
+                Outcome::Success
+            };
+        }
+        if stop {
+            stop_daemon(&project_root);
+            return Outcome::Success;
+        }
+        return run_tool(dmypy_command(&status_file, &["status"]), &ExitCodes::mypy());
+    }
+
+    // Load the project's pyproject.toml
+    let materialized = Profile::load(None).unwrap().materialize(None).unwrap();
+    let cache = if no_cache || restart || update_baseline {
+        None
+    } else {
+        Cache::new(&materialized, "mypy")
+    };
+    if let Some(cache) = &cache {
+        if cache.contains(&cache.key(&files)) {
+            return report_cached("mypy", format);
+        }
+    }
+    let key = cache.as_ref().map(|x| x.key(&files));
+
+    let config_file = materialized.file_arg();
+    let structured =
+        format != OutputFormat::Text || update_baseline || baseline_file(&materialized).exists();
+
+    // The daemon does not pick up changes to the configuration file, so we restart it if the
+    // materialized configuration differs from the one the daemon was started with.
+    let config_hash = sha256_hex(std::fs::read(&materialized.file).unwrap());
+    let hash_file = config_hash_file(&project_root);
+    let previous_hash = std::fs::read_to_string(&hash_file).ok();
+    if restart {
+        stop_daemon(&project_root);
+    } else if status_file.exists() && previous_hash.as_deref() != Some(&config_hash) {
+        eprintln!("[tire] Configuration changed, restarting dmypy daemon.");
+        stop_daemon(&project_root);
+    }
+    write_if_changed(&hash_file, &config_hash).unwrap();
+
+    // Run dmypy with the merged config file. Column numbers are always enabled and pretty output is
+    // always disabled (which we can't parse), as changing the options would restart the daemon when
+    // switching between output formats.
+    let mut uv_command = dmypy_command(
+        &status_file,
+        &[
+            "run",
+            "--",
+            "--config-file",
+            &config_file,
+            "--show-column-numbers",
+            "--no-pretty",
+        ],
+    );
     if files.is_empty() {
         uv_command.push(".".to_owned());
     } else {
-        uv_command.extend(files);
+        uv_command.extend(files.iter().cloned());
+    }
+
+    let outcome = run_mypy(
+        uv_command,
+        &files,
+        structured,
+        format,
+        update_baseline,
+        &materialized,
+    );
+    if let (Outcome::Success, Some(cache), Some(key)) = (outcome, &cache, &key) {
+        cache.insert(key);
+    }
+    outcome
+}
+
+fn run_mypy(
+    uv_command: Vec<String>,
+    files: &[String],
+    structured: bool,
+    format: OutputFormat,
+    update_baseline: bool,
+    materialized: &Materialized,
+) -> Outcome {
+    if !structured {
+        return run_tool(uv_command, &ExitCodes::mypy());
     }
 
-    // Run the command
-    run_command_or_exit(uv_command);
+    // Convert mypy's diagnostics into the requested format, leaving out those in the baseline.
+    let (status, stdout) = capture_command(uv_command);
+    let outcome = ExitCodes::mypy().outcome(status.code());
+    if outcome == Outcome::Error {
+        print!("{stdout}");
+        return outcome;
+    }
+    let diagnostics = parse_mypy_output(&stdout);
+    baseline::report(
+        "mypy",
+        diagnostics,
+        files,
+        format,
+        update_baseline,
+        materialized,
+    )
 }
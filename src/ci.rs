@@ -0,0 +1,260 @@
+//! Implements the `tire ci` command.
+//!
+//! Runs the steps of a typical CI pipeline: checking the formatting, linting, type-checking and
+//! testing. Each step runs as a separate `tire` process, so that independent steps can run
+//! concurrently. Concurrent steps buffer their output and print it in one piece when they complete,
+//! wrapped in the log group markers of the CI system, so that the output of steps never
+//! interleaves.
+
+use std::{
+    io::Read,
+    path::Path,
+    process::{Command, Stdio},
+    sync::{Mutex, mpsc},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    profile::Profile,
+    utils::{ExitCodes, Outcome},
+};
+
+/// A step of `tire ci`.
+#[derive(Clone, Copy, Debug)]
+pub struct Step {
+    /// The name of the step, which is also the name of its log group.
+    pub name: &'static str,
+
+    /// The arguments to run the step with `tire`.
+    pub args: &'static [&'static str],
+}
+
+/// The steps of `tire ci`, in the order in which they are started.
+pub const STEPS: [Step; 4] = [
+    Step {
+        name: "fmt",
+        args: &["fmt", "--check"],
+    },
+    Step {
+        name: "lint",
+        args: &["lint"],
+    },
+    Step {
+        name: "check",
+        args: &["check"],
+    },
+    Step {
+        name: "test",
+        args: &["test"],
+    },
+];
+
+/// How to mark the start and end of the output of a step, so that CI systems can collapse it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogGroups {
+    /// `::group::` and `::endgroup::` workflow commands.
+    GitHub,
+
+    /// `section_start` and `section_end` markers.
+    GitLab,
+
+    /// A plain header line.
+    Plain,
+}
+
+impl LogGroups {
+    /// Detect the CI system from the environment variables that it sets.
+    pub fn detect() -> Self {
+        if std::env::var("GITHUB_ACTIONS").is_ok_and(|x| x == "true") {
+            LogGroups::GitHub
+        } else if std::env::var_os("GITLAB_CI").is_some() {
+            LogGroups::GitLab
+        } else {
+            LogGroups::Plain
+        }
+    }
+
+    /// Returns the line that starts the group of the named step. *timestamp* is in seconds since
+    /// the Unix epoch.
+    pub fn start(self, name: &str, timestamp: u64) -> String {
+        match self {
+            LogGroups::GitHub => format!("::group::{name}"),
+            LogGroups::GitLab => {
+                format!("\x1b[0Ksection_start:{timestamp}:{name}\r\x1b[0Ktire {name}")
+            }
+            LogGroups::Plain => format!("[tire] ==> {name}"),
+        }
+    }
+
+    /// Returns the line that ends the group of the named step, if the CI system needs one.
+    pub fn end(self, name: &str, timestamp: u64) -> Option<String> {
+        match self {
+            LogGroups::GitHub => Some("::endgroup::".to_owned()),
+            LogGroups::GitLab => Some(format!("\x1b[0Ksection_end:{timestamp}:{name}\r\x1b[0K")),
+            LogGroups::Plain => None,
+        }
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
+}
+
+/// The result of a completed step.
+pub struct Completed {
+    pub step: Step,
+    pub outcome: Outcome,
+    pub duration: Duration,
+
+    /// The combined standard output and error, if it was buffered.
+    pub output: Option<String>,
+}
+
+/// Sorts the completed steps in the order of [STEPS]. Concurrent steps complete in any order, but
+/// the summary should always look the same.
+pub fn sort_by_step(completed: &mut [Completed]) {
+    completed.sort_by_key(|x| STEPS.iter().position(|step| step.name == x.step.name));
+}
+
+/// Returns the most severe outcome of the completed steps.
+pub fn overall_outcome(completed: &[Completed]) -> Outcome {
+    completed
+        .iter()
+        .map(|x| x.outcome)
+        .max()
+        .unwrap_or(Outcome::Success)
+}
+
+fn step_command(tire: &Path, step: Step) -> Command {
+    let mut command = Command::new(tire);
+    command.args(step.args).stdin(Stdio::null());
+    command
+}
+
+fn step_outcome(step: Step, status: std::io::Result<std::process::ExitStatus>) -> Outcome {
+    match status {
+        Ok(status) => ExitCodes::new("tire")
+            .with(1, Outcome::Failure)
+            .outcome(status.code()),
+        Err(err) => {
+            eprintln!("[tire] Failed to run step `{}`: {err}", step.name);
+            Outcome::Error
+        }
+    }
+}
+
+/// Run a step with its output passed through.
+fn run_step(tire: &Path, step: Step) -> Completed {
+    let start = Instant::now();
+    let status = step_command(tire, step).status();
+    Completed {
+        step,
+        outcome: step_outcome(step, status),
+        duration: start.elapsed(),
+        output: None,
+    }
+}
+
+/// Run a step and buffer its output. Standard output and error are written to the same file, so
+/// that they stay in order.
+fn run_step_buffered(tire: &Path, step: Step) -> Completed {
+    let start = Instant::now();
+    let log =
+        std::env::temp_dir().join(format!("tire-ci-{}-{}.log", std::process::id(), step.name));
+    let status = std::fs::File::create(&log).and_then(|file| {
+        let stderr = file.try_clone()?;
+        step_command(tire, step)
+            .stdout(file)
+            .stderr(stderr)
+            .status()
+    });
+    let mut output = String::new();
+    if let Ok(mut file) = std::fs::File::open(&log) {
+        let mut bytes = Vec::new();
+        let _ = file.read_to_end(&mut bytes);
+        output = String::from_utf8_lossy(&bytes).to_string();
+    }
+    let _ = std::fs::remove_file(&log);
+    Completed {
+        step,
+        outcome: step_outcome(step, status),
+        duration: start.elapsed(),
+        output: Some(output),
+    }
+}
+
+fn print_summary(completed: &[Completed]) {
+    for x in completed {
+        let result = match x.outcome {
+            Outcome::Success => "passed",
+            Outcome::Failure => "failed",
+            Outcome::Error => "errored",
+        };
+        eprintln!(
+            "[tire] {:<6} {result} in {:.1}s",
+            x.step.name,
+            x.duration.as_secs_f64()
+        );
+    }
+}
+
+pub fn ci(jobs: usize) -> Outcome {
+    // Write the merged configuration once up front, so that the steps don't race to write it.
+    Profile::load(None).unwrap().materialize(None).unwrap();
+    let tire = std::env::current_exe().unwrap();
+    let groups = LogGroups::detect();
+
+    let mut completed = Vec::new();
+    if jobs <= 1 {
+        for step in STEPS {
+            println!("{}", groups.start(step.name, unix_timestamp()));
+            let result = run_step(&tire, step);
+            if let Some(end) = groups.end(step.name, unix_timestamp()) {
+                println!("{end}");
+            }
+            completed.push(result);
+        }
+    } else {
+        let queue = Mutex::new(STEPS.into_iter());
+        let (sender, receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.min(STEPS.len()) {
+                let (queue, sender, tire) = (&queue, sender.clone(), &tire);
+                scope.spawn(move || {
+                    loop {
+                        let Some(step) = queue.lock().unwrap().next() else {
+                            break;
+                        };
+                        if sender.send(run_step_buffered(tire, step)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            // Print the output of each step as soon as it completes.
+            for result in receiver {
+                let end = unix_timestamp();
+                let start = end.saturating_sub(result.duration.as_secs());
+                println!("{}", groups.start(result.step.name, start));
+                let output = result.output.as_deref().unwrap_or_default();
+                print!("{output}");
+                if !output.is_empty() && !output.ends_with('\n') {
+                    println!();
+                }
+                if let Some(end) = groups.end(result.step.name, end) {
+                    println!("{end}");
+                }
+                completed.push(result);
+            }
+        });
+    }
+
+    sort_by_step(&mut completed);
+    print_summary(&completed);
+    overall_outcome(&completed)
+}
@@ -0,0 +1,17 @@
+//! Implements the `tire clean` command.
+
+use crate::{check, profile::state_dir, utils::find_project_root};
+
+pub fn clean() {
+    let project_root = find_project_root(None).unwrap();
+
+    // Stop the dmypy daemon before removing its status file, otherwise it keeps running.
+    check::stop_daemon(&project_root);
+    check::stop_legacy_daemon(&project_root);
+
+    let state_dir = state_dir(&project_root);
+    if state_dir.exists() {
+        eprintln!("[tire] Removing {}", state_dir.display());
+        std::fs::remove_dir_all(state_dir).unwrap();
+    }
+}
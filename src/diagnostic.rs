@@ -0,0 +1,397 @@
+//! A common representation of the diagnostics reported by `ruff` and `mypy`, and renderers for
+//! the output formats understood by CI systems.
+
+use std::{collections::BTreeMap, path::Path};
+
+use quick_xml::escape::escape;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::sha256_hex;
+
+/// Output formats for diagnostics, selected with `--format`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// The tool's own human-readable output.
+    #[default]
+    Text,
+
+    /// GitHub Actions workflow commands, shown as inline annotations in pull requests.
+    Github,
+
+    /// SARIF 2.1.0, e.g. for GitHub code scanning.
+    Sarif,
+
+    /// A JSON array of diagnostics.
+    Json,
+
+    /// JUnit XML, with one test case per diagnostic.
+    Junit,
+
+    /// GitLab Code Quality report.
+    Gitlab,
+}
+
+/// The severity of a [Diagnostic].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A single problem reported by a tool.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// The name of the tool that reported the problem, e.g. `ruff`.
+    pub tool: String,
+
+    /// The path of the affected file, relative to the current working directory if possible.
+    pub file: String,
+
+    /// The 1-based line number.
+    pub line: usize,
+
+    /// The 1-based column number.
+    pub column: usize,
+
+    /// The 1-based line number where the problem ends, if known.
+    pub end_line: Option<usize>,
+
+    /// The 1-based column number where the problem ends, if known.
+    pub end_column: Option<usize>,
+
+    /// The rule or error code, e.g. `F401` or `arg-type`.
+    pub code: Option<String>,
+
+    /// The human-readable description of the problem.
+    pub message: String,
+
+    /// The severity of the problem.
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    /// Returns the code of the diagnostic, or `unknown` if it has none.
+    pub fn code_or_unknown(&self) -> &str {
+        self.code.as_deref().unwrap_or("unknown")
+    }
+}
+
+/// Returns the path relative to the current working directory, if it is inside of it.
+fn relative_path(path: &str) -> String {
+    let cwd = std::env::current_dir().unwrap();
+    Path::new(path)
+        .strip_prefix(&cwd)
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_owned())
+}
+
+#[derive(Deserialize)]
+struct RuffLocation {
+    row: usize,
+    column: usize,
+}
+
+#[derive(Deserialize)]
+struct RuffDiagnostic {
+    code: Option<String>,
+    filename: String,
+    location: RuffLocation,
+    end_location: Option<RuffLocation>,
+    message: String,
+}
+
+/// Parse the output of `ruff check --output-format json`.
+pub fn parse_ruff_json(json: &str) -> Result<Vec<Diagnostic>, serde_json::Error> {
+    let diagnostics: Vec<RuffDiagnostic> = serde_json::from_str(json)?;
+    Ok(diagnostics
+        .into_iter()
+        .map(|x| Diagnostic {
+            tool: "ruff".to_owned(),
+            file: relative_path(&x.filename),
+            line: x.location.row,
+            column: x.location.column,
+            end_line: x.end_location.as_ref().map(|x| x.row),
+            end_column: x.end_location.as_ref().map(|x| x.column),
+            // Ruff reports syntax errors without a code.
+            code: x.code,
+            message: x.message,
+            severity: Severity::Error,
+        })
+        .collect())
+}
+
+/// Parse the output of `mypy --show-column-numbers --no-pretty`, which reports diagnostics in the
+/// form `file:line:column: severity: message  [code]`. Lines that do not refer to a line in a
+/// file (e.g. the summary or `In function "f":` context notes) are ignored.
+pub fn parse_mypy_output(output: &str) -> Vec<Diagnostic> {
+    output.lines().filter_map(parse_mypy_line).collect()
+}
+
+fn parse_mypy_line(line: &str) -> Option<Diagnostic> {
+    let (location, severity, rest) = [
+        (": error: ", Severity::Error),
+        (": warning: ", Severity::Warning),
+        (": note: ", Severity::Note),
+    ]
+    .into_iter()
+    .find_map(|(separator, severity)| {
+        line.split_once(separator)
+            .map(|(location, rest)| (location, severity, rest))
+    })?;
+
+    // The location is `file:line[:column]`, where the file may contain colons itself.
+    let parts: Vec<&str> = location.rsplitn(3, ':').collect();
+    let (file, line, column) = match parts.as_slice() {
+        [column, line, file]
+            if line.parse::<usize>().is_ok() && column.parse::<usize>().is_ok() =>
+        {
+            (*file, line.parse().ok()?, column.parse().ok()?)
+        }
+        _ => {
+            let (file, line) = location.rsplit_once(':')?;
+            (file, line.parse().ok()?, 1)
+        }
+    };
+
+    // The code is appended to the message as `  [code]`.
+    let (message, code) = match rest.trim_end().strip_suffix(']') {
+        Some(stripped) => match stripped.rsplit_once("  [") {
+            Some((message, code)) => (message, Some(code.to_owned())),
+            None => (rest.trim_end(), None),
+        },
+        None => (rest.trim_end(), None),
+    };
+
+    Some(Diagnostic {
+        tool: "mypy".to_owned(),
+        file: relative_path(file),
+        line,
+        column,
+        end_line: None,
+        end_column: None,
+        code,
+        message: message.to_owned(),
+        severity,
+    })
+}
+
+/// Render the diagnostics in the given format. Usually, [OutputFormat::Text] is rendered by the
+/// tools themselves, but Tire needs to render it if it filters the diagnostics, e.g. with a
+/// baseline.
+pub fn render(format: OutputFormat, diagnostics: &[Diagnostic]) -> String {
+    match format {
+        OutputFormat::Text => render_text(diagnostics),
+        OutputFormat::Github => render_github(diagnostics),
+        OutputFormat::Sarif => render_sarif(diagnostics) + "\n",
+        OutputFormat::Json => serde_json::to_string_pretty(diagnostics).unwrap() + "\n",
+        OutputFormat::Junit => render_junit(diagnostics),
+        OutputFormat::Gitlab => render_gitlab(diagnostics) + "\n",
+    }
+}
+
+/// Renders one line per diagnostic in the form `file:line:column: code message`.
+fn render_text(diagnostics: &[Diagnostic]) -> String {
+    let mut output = String::new();
+    for x in diagnostics {
+        output += &format!(
+            "{}:{}:{}: {} {}\n",
+            x.file,
+            x.line,
+            x.column,
+            x.code_or_unknown(),
+            x.message
+        );
+    }
+    output
+}
+
+/// Renders GitHub Actions workflow commands, see
+/// <https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions>.
+fn render_github(diagnostics: &[Diagnostic]) -> String {
+    fn escape_data(s: &str) -> String {
+        s.replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A")
+    }
+    fn escape_property(s: &str) -> String {
+        escape_data(s).replace(':', "%3A").replace(',', "%2C")
+    }
+
+    let mut output = String::new();
+    for diagnostic in diagnostics {
+        let command = match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "notice",
+        };
+        let mut properties = vec![
+            format!("file={}", escape_property(&diagnostic.file)),
+            format!("line={}", diagnostic.line),
+            format!("col={}", diagnostic.column),
+        ];
+        if let Some(end_line) = diagnostic.end_line {
+            properties.push(format!("endLine={end_line}"));
+        }
+        if let Some(end_column) = diagnostic.end_column {
+            properties.push(format!("endColumn={end_column}"));
+        }
+        let title = match &diagnostic.code {
+            Some(code) => format!("{} ({code})", diagnostic.tool),
+            None => diagnostic.tool.clone(),
+        };
+        properties.push(format!("title={}", escape_property(&title)));
+        output += &format!(
+            "::{command} {}::{}\n",
+            properties.join(","),
+            escape_data(&diagnostic.message)
+        );
+    }
+    output
+}
+
+/// Renders a SARIF 2.1.0 log with one run per tool.
+fn render_sarif(diagnostics: &[Diagnostic]) -> String {
+    let mut by_tool: BTreeMap<&str, Vec<&Diagnostic>> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        by_tool
+            .entry(&diagnostic.tool)
+            .or_default()
+            .push(diagnostic);
+    }
+
+    let runs: Vec<serde_json::Value> = by_tool
+        .into_iter()
+        .map(|(tool, diagnostics)| {
+            let mut rules: Vec<&str> = diagnostics.iter().map(|x| x.code_or_unknown()).collect();
+            rules.sort();
+            rules.dedup();
+            let results: Vec<serde_json::Value> = diagnostics
+                .iter()
+                .map(|x| {
+                    let mut region = serde_json::json!({
+                        "startLine": x.line,
+                        "startColumn": x.column,
+                    });
+                    if let Some(end_line) = x.end_line {
+                        region["endLine"] = end_line.into();
+                    }
+                    if let Some(end_column) = x.end_column {
+                        region["endColumn"] = end_column.into();
+                    }
+                    serde_json::json!({
+                        "ruleId": x.code_or_unknown(),
+                        "level": match x.severity {
+                            Severity::Error => "error",
+                            Severity::Warning => "warning",
+                            Severity::Note => "note",
+                        },
+                        "message": { "text": x.message },
+                        "locations": [{
+                            "physicalLocation": {
+                                "artifactLocation": { "uri": x.file.replace('\\', "/") },
+                                "region": region,
+                            }
+                        }],
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "tool": {
+                    "driver": {
+                        "name": tool,
+                        "rules": rules.iter().map(|x| serde_json::json!({ "id": x })).collect::<Vec<_>>(),
+                    }
+                },
+                "results": results,
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": runs,
+    }))
+    .unwrap()
+}
+
+/// Renders a JUnit XML report with one test suite per file and one failing test case per
+/// diagnostic.
+fn render_junit(diagnostics: &[Diagnostic]) -> String {
+    let mut by_file: BTreeMap<&str, Vec<&Diagnostic>> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        by_file
+            .entry(&diagnostic.file)
+            .or_default()
+            .push(diagnostic);
+    }
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output += &format!(
+        "<testsuites name=\"tire\" tests=\"{0}\" failures=\"{0}\" errors=\"0\">\n",
+        diagnostics.len()
+    );
+    for (file, diagnostics) in by_file {
+        output += &format!(
+            "  <testsuite name=\"{}\" tests=\"{1}\" failures=\"{1}\" errors=\"0\">\n",
+            escape(file),
+            diagnostics.len()
+        );
+        for x in diagnostics {
+            let location = format!("{}:{}:{}", x.file, x.line, x.column);
+            output += &format!(
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" line=\"{}\">\n",
+                escape(format!("{} {} at {location}", x.tool, x.code_or_unknown())),
+                escape(&x.file),
+                escape(&x.file),
+                x.line
+            );
+            output += &format!(
+                "      <failure message=\"{}\" type=\"{}\">{}: {}</failure>\n",
+                escape(&x.message),
+                escape(x.code_or_unknown()),
+                escape(&location),
+                escape(&x.message)
+            );
+            output += "    </testcase>\n";
+        }
+        output += "  </testsuite>\n";
+    }
+    output += "</testsuites>\n";
+    output
+}
+
+/// Renders a GitLab Code Quality report, see
+/// <https://docs.gitlab.com/ci/testing/code_quality/#code-quality-report-format>.
+fn render_gitlab(diagnostics: &[Diagnostic]) -> String {
+    let issues: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|x| {
+            let fingerprint = sha256_hex(format!(
+                "{}:{}:{}:{}:{}",
+                x.tool,
+                x.file,
+                x.code_or_unknown(),
+                x.line,
+                x.message
+            ));
+            serde_json::json!({
+                "description": format!("{}: {}", x.code_or_unknown(), x.message),
+                "check_name": format!("{}/{}", x.tool, x.code_or_unknown()),
+                "fingerprint": fingerprint,
+                "severity": match x.severity {
+                    Severity::Error => "major",
+                    Severity::Warning => "minor",
+                    Severity::Note => "info",
+                },
+                "location": {
+                    "path": x.file.replace('\\', "/"),
+                    "lines": { "begin": x.line },
+                },
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&issues).unwrap()
+}
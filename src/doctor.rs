@@ -0,0 +1,372 @@
+//! Implements the `tire doctor` command.
+//!
+//! Runs a series of checks on the environment and the project and reports each with a status and,
+//! if something is off, a suggestion on how to fix it.
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use toml::Table;
+
+use crate::{
+    check::{config_hash_file, legacy_status_file, status_file},
+    profile::{Profile, configured_profile, state_dir},
+    utils::{Outcome, command_output, find_pyproject_toml, sha256_hex},
+};
+
+/// Files that Tire writes to the state directory.
+const STATE_FILES: [&str; 9] = [
+    ".gitignore",
+    "cache",
+    "pyproject.toml",
+    "dmypy.json",
+    "dmypy.sha256",
+    "test-durations.json",
+    "junit.xml",
+    "junit-retry.xml",
+    "test-report.json",
+];
+
+/// The status of a [Check], ordered by severity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// The result of a single check performed by `tire doctor`.
+#[derive(Clone, Debug)]
+pub struct Check {
+    /// A short name of what was checked, e.g. `uv`.
+    pub name: String,
+
+    pub status: Status,
+
+    /// What was found.
+    pub detail: String,
+
+    /// A suggestion on how to fix the problem, if the check did not pass.
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_owned(),
+            status: Status::Pass,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.to_owned(),
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.to_owned(),
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self.status {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+        };
+        write!(f, "[{status}] {}: {}", self.name, self.detail)?;
+        if let Some(fix) = &self.fix {
+            write!(f, "\n       fix: {fix}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the outcome of `tire doctor` for the given checks, which only fails on [Status::Fail].
+pub fn outcome(checks: &[Check]) -> Outcome {
+    match checks.iter().map(|x| x.status).max() {
+        Some(Status::Fail) => Outcome::Failure,
+        _ => Outcome::Success,
+    }
+}
+
+fn check_uv(cwd: &Path) -> Check {
+    match command_output("uv", &["--version"], cwd) {
+        Some(version) => Check::pass("uv", version),
+        None => Check::fail(
+            "uv",
+            "`uv` was not found on the PATH",
+            "install uv, see https://docs.astral.sh/uv/getting-started/installation/",
+        ),
+    }
+}
+
+fn check_python(cwd: &Path) -> Check {
+    let Some(output) = command_output("uv", &["python", "list", "--only-installed"], cwd) else {
+        return Check::fail(
+            "python",
+            "failed to list Python versions with `uv python list`",
+            "make sure `uv` works",
+        );
+    };
+    let versions: Vec<&str> = output
+        .lines()
+        .filter_map(|x| x.split_whitespace().next())
+        .collect();
+    if versions.is_empty() {
+        return Check::warn(
+            "python",
+            "no Python versions are installed",
+            "run `uv python install`",
+        );
+    }
+    Check::pass("python", versions.join(", "))
+}
+
+/// Returns the root of the Uv workspace that the project belongs to, if any.
+fn workspace_root(project_root: &Path) -> Option<PathBuf> {
+    project_root.ancestors().find_map(|dir| {
+        let content = std::fs::read_to_string(dir.join("pyproject.toml")).ok()?;
+        let table: Table = content.parse().ok()?;
+        table.get("tool")?.get("uv")?.get("workspace")?;
+        Some(dir.to_path_buf())
+    })
+}
+
+fn check_pyproject(pyproject_file: Option<&Path>) -> Vec<Check> {
+    let Some(file) = pyproject_file else {
+        return vec![Check::fail(
+            "pyproject.toml",
+            "no `pyproject.toml` found in the current directory or its parents",
+            "run `tire init` to create a project",
+        )];
+    };
+    let mut checks = vec![Check::pass("pyproject.toml", file.display().to_string())];
+    let project_root = file.parent().unwrap();
+    checks.push(match workspace_root(project_root) {
+        Some(root) if root == project_root => {
+            Check::pass("workspace", "the project is the root of a Uv workspace")
+        }
+        Some(root) => Check::warn(
+            "workspace",
+            format!(
+                "the project is a member of the Uv workspace at {}",
+                root.display()
+            ),
+            "Tire does not support workspaces yet and uses the member's configuration only",
+        ),
+        None => Check::pass("workspace", "not part of a Uv workspace"),
+    });
+    checks
+}
+
+fn check_profile(pyproject: &Table) -> Check {
+    if let Some(name) = configured_profile(pyproject).filter(|x| *x != "default") {
+        return match Profile::load(Some(name.to_owned())) {
+            Ok(_) => Check::warn(
+                "profile",
+                format!("{name} (fetched, not cached)"),
+                "Tire fetches remote profiles on every run, so it needs network access",
+            ),
+            Err(err) => Check::fail(
+                "profile",
+                format!("failed to load profile {name:?}: {err}"),
+                "check the `[tool.tire].profile` setting",
+            ),
+        };
+    }
+    match Profile::load(None) {
+        Ok(profile) => Check::pass("profile", format!("{} (embedded)", profile.name)),
+        Err(err) => Check::fail(
+            "profile",
+            format!("failed to load the default profile: {err}"),
+            "reinstall Tire",
+        ),
+    }
+}
+
+fn check_tools(project_root: &Path) -> Vec<Check> {
+    ["ruff", "mypy", "pytest"]
+        .into_iter()
+        .map(|tool| {
+            match command_output(
+                "uv",
+                &["run", "--with", tool, tool, "--version"],
+                project_root,
+            ) {
+                Some(version) => Check::pass(tool, version.lines().next().unwrap_or_default()),
+                None => Check::fail(
+                    tool,
+                    format!("failed to run `{tool} --version` with `uv run`"),
+                    "check your network connection and that `uv sync` succeeds",
+                ),
+            }
+        })
+        .collect()
+}
+
+fn check_state_dir(project_root: &Path) -> Vec<Check> {
+    let dir = state_dir(project_root);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return vec![Check::pass(
+            "state",
+            format!("{} does not exist yet", dir.display()),
+        )];
+    };
+
+    let mut checks = Vec::new();
+    let unknown: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|x| x.file_name().to_string_lossy().to_string())
+        .filter(|x| !STATE_FILES.contains(&x.as_str()))
+        .collect();
+    if unknown.is_empty() {
+        checks.push(Check::pass("state", dir.display().to_string()));
+    } else {
+        checks.push(Check::warn(
+            "state",
+            format!(
+                "unexpected files in {}: {}",
+                dir.display(),
+                unknown.join(", ")
+            ),
+            "run `tire clean` to remove Tire's state",
+        ));
+    }
+
+    let legacy = legacy_status_file(project_root);
+    if legacy.exists() {
+        checks.push(Check::warn(
+            "state",
+            format!(
+                "{} was left behind by an older version of Tire",
+                legacy.display()
+            ),
+            "run `tire clean` to stop its daemon and remove it",
+        ));
+    }
+
+    let hash_file = config_hash_file(project_root);
+    if hash_file.exists() && !status_file(project_root).exists() {
+        checks.push(Check::warn(
+            "state",
+            format!(
+                "{} refers to a dmypy daemon that is gone",
+                hash_file.display()
+            ),
+            "run `tire clean` to remove Tire's state",
+        ));
+    }
+    checks
+}
+
+fn check_daemon(project_root: &Path, config_hash: Option<&str>) -> Check {
+    let status_file = status_file(project_root);
+    if !status_file.exists() {
+        return Check::pass("dmypy", "no daemon is running");
+    }
+    let status_arg = status_file.to_string_lossy();
+    let running = command_output(
+        "uv",
+        &[
+            "run",
+            "--with",
+            "mypy",
+            "dmypy",
+            "--status-file",
+            &status_arg,
+            "status",
+        ],
+        project_root,
+    )
+    .is_some();
+    if !running {
+        return Check::warn(
+            "dmypy",
+            format!(
+                "{} exists, but the daemon is not responding",
+                status_file.display()
+            ),
+            "run `tire check --restart`, or `tire clean` to remove Tire's state",
+        );
+    }
+    let started_with = std::fs::read_to_string(config_hash_file(project_root)).ok();
+    if config_hash.is_some() && started_with.as_deref() != config_hash {
+        return Check::warn(
+            "dmypy",
+            "the daemon is running with an outdated configuration",
+            "run `tire check`, which restarts it automatically, or `tire check --restart`",
+        );
+    }
+    Check::pass("dmypy", "the daemon is running")
+}
+
+/// Run all checks.
+pub fn checks() -> Vec<Check> {
+    let cwd = std::env::current_dir().unwrap();
+    let mut checks = vec![check_uv(&cwd)];
+    let has_uv = checks[0].status == Status::Pass;
+    if has_uv {
+        checks.push(check_python(&cwd));
+    }
+
+    let pyproject_file = find_pyproject_toml(Some(cwd.clone()));
+    checks.extend(check_pyproject(pyproject_file.as_deref()));
+    let pyproject: Table = match pyproject_file.as_deref().map(std::fs::read_to_string) {
+        Some(Ok(content)) => match content.parse() {
+            Ok(table) => table,
+            Err(err) => {
+                checks.push(Check::fail(
+                    "pyproject.toml",
+                    format!("failed to parse: {err}"),
+                    "fix the syntax of your `pyproject.toml`",
+                ));
+                Table::new()
+            }
+        },
+        _ => Table::new(),
+    };
+    checks.push(check_profile(&pyproject));
+
+    let project_root = pyproject_file
+        .as_deref()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or(cwd);
+    if has_uv {
+        checks.extend(check_tools(&project_root));
+    }
+
+    // The hash of the configuration that the next `tire check` would start the daemon with. It is
+    // computed in memory, as the doctor must not write the materialized file.
+    let config_hash = Profile::load(None)
+        .ok()
+        .and_then(|profile| profile.merged_config(Some(project_root.clone())).ok())
+        .and_then(|(_, config)| toml::to_string(&config).ok())
+        .map(sha256_hex);
+    checks.extend(check_state_dir(&project_root));
+    checks.push(check_daemon(&project_root, config_hash.as_deref()));
+    checks
+}
+
+pub fn doctor() -> Outcome {
+    let checks = checks();
+    for check in &checks {
+        println!("{check}");
+    }
+    outcome(&checks)
+}
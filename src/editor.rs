@@ -0,0 +1,337 @@
+//! Implements the `tire editor` command.
+//!
+//! Editors read the project's `pyproject.toml` rather than the materialized configuration, so
+//! their language servers would lint and type-check with different settings than Tire. This
+//! command points the editor's integrations at the materialized configuration instead. Existing
+//! settings files are merged with, rather than replaced, and keys that Tire doesn't manage are left
+//! untouched.
+
+use std::path::Path;
+
+use serde_json::{Map, Value as JsonValue, json};
+use toml::{Table, Value};
+
+use crate::{profile::Profile, utils::Outcome};
+
+/// The editors that `tire editor` can configure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Editor {
+    /// Visual Studio Code with the Python, Ruff and Mypy Type Checker extensions.
+    Vscode,
+
+    /// PyCharm, which keeps its settings in IDE-managed XML files. Tire prints the settings to
+    /// apply instead of writing them.
+    Pycharm,
+
+    /// Helix, using the Ruff language server and `tire fmt` for formatting.
+    Helix,
+}
+
+/// The language servers that Helix uses for Python by default, as of Helix 25.01. Setting
+/// `language-servers` replaces the default, so Tire lists them along with Ruff.
+const HELIX_PYTHON_SERVERS: [&str; 3] = ["ruff", "jedi", "pylsp"];
+
+/// Returns the path of the Python interpreter in the project's virtual environment, relative to
+/// the project root.
+fn venv_python() -> &'static str {
+    if cfg!(windows) {
+        ".venv/Scripts/python.exe"
+    } else {
+        ".venv/bin/python"
+    }
+}
+
+/// Returns the VS Code settings that Tire manages, given the path of the materialized
+/// configuration file relative to the workspace folder.
+pub fn vscode_settings(config_file: &str) -> Map<String, JsonValue> {
+    let config_file = if Path::new(config_file).is_absolute() {
+        config_file.to_owned()
+    } else {
+        format!("${{workspaceFolder}}/{config_file}")
+    };
+    let settings = json!({
+        "python.defaultInterpreterPath": format!("${{workspaceFolder}}/{}", venv_python()),
+        "ruff.configuration": config_file,
+        "mypy-type-checker.args": [format!("--config-file={config_file}")],
+        "python.testing.pytestEnabled": true,
+        "python.testing.pytestArgs": ["-c", config_file, "--rootdir", "${workspaceFolder}"],
+    });
+    match settings {
+        JsonValue::Object(map) => map,
+        _ => unreachable!(),
+    }
+}
+
+/// Merges *settings* into *existing*, returning the keys whose values changed. Arrays of command
+/// line arguments are merged with [merge_args], so that the user's own arguments are kept.
+pub fn merge_json(
+    existing: &mut Map<String, JsonValue>,
+    settings: Map<String, JsonValue>,
+) -> Vec<String> {
+    let mut changed = Vec::new();
+    for (key, value) in settings {
+        let value = match (existing.get(&key), value) {
+            (Some(JsonValue::Array(args)), JsonValue::Array(managed)) => {
+                JsonValue::Array(merge_args(args, &managed))
+            }
+            (_, value) => value,
+        };
+        if existing.get(&key) != Some(&value) {
+            changed.push(key.clone());
+            existing.insert(key, value);
+        }
+    }
+    changed
+}
+
+/// Merges the *managed* command line arguments into the existing *args*. Existing occurrences of
+/// the options in *managed* (e.g. `--config-file=<file>` or `-c <file>`) are replaced, all other
+/// arguments are kept after the managed ones.
+pub fn merge_args(args: &[JsonValue], managed: &[JsonValue]) -> Vec<JsonValue> {
+    // The options that Tire manages, and whether they take their value as a separate argument.
+    let mut options: Vec<(&str, bool)> = Vec::new();
+    for (index, arg) in managed.iter().enumerate() {
+        let Some(arg) = arg.as_str().filter(|x| x.starts_with('-')) else {
+            continue;
+        };
+        let name = arg.split('=').next().unwrap();
+        let separate = !arg.contains('=')
+            && managed
+                .get(index + 1)
+                .and_then(JsonValue::as_str)
+                .is_some_and(|x| !x.starts_with('-'));
+        options.push((name, separate));
+        // Pytest accepts `-c` and `--config-file` for the same option.
+        match name {
+            "-c" => options.push(("--config-file", separate)),
+            "--config-file" => options.push(("-c", separate)),
+            _ => {}
+        }
+    }
+
+    let mut merged = managed.to_vec();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let option = arg.as_str().and_then(|arg| {
+            options
+                .iter()
+                .find(|(name, _)| arg == *name || arg.starts_with(&format!("{name}=")))
+                .map(|(name, separate)| *separate && arg == *name)
+        });
+        match option {
+            Some(true) => {
+                iter.next();
+            }
+            Some(false) => {}
+            None => merged.push(arg.clone()),
+        }
+    }
+    merged
+}
+
+/// Merges the Helix settings that Tire manages into the given `languages.toml`, given the path of
+/// the materialized configuration file relative to the project root. Returns whether anything
+/// changed.
+pub fn merge_helix(languages: &mut Table, config_file: &str) -> bool {
+    let before = languages.clone();
+
+    // Point the Ruff language server at the materialized configuration.
+    let servers = table_entry(languages, "language-server");
+    let ruff = table_entry(servers, "ruff");
+    ruff.insert("command".to_owned(), "ruff".into());
+    ruff.insert("args".to_owned(), Value::Array(vec!["server".into()]));
+    let settings = table_entry(table_entry(ruff, "config"), "settings");
+    settings.insert("configuration".to_owned(), config_file.into());
+
+    // Use Ruff for Python and format with `tire fmt`, which also sorts imports.
+    let languages_array = languages
+        .entry("language")
+        .or_insert_with(|| Value::Array(vec![]));
+    if !languages_array.is_array() {
+        *languages_array = Value::Array(vec![]);
+    }
+    let array = languages_array.as_array_mut().unwrap();
+    let index = match array
+        .iter()
+        .position(|x| x.get("name").and_then(Value::as_str) == Some("python"))
+    {
+        Some(index) => index,
+        None => {
+            let mut python = Table::new();
+            python.insert("name".to_owned(), "python".into());
+            array.push(Value::Table(python));
+            array.len() - 1
+        }
+    };
+    let python = array[index].as_table_mut().unwrap();
+    let servers = python.entry("language-servers").or_insert_with(|| {
+        Value::Array(HELIX_PYTHON_SERVERS.into_iter().map(Value::from).collect())
+    });
+    if let Some(servers) = servers.as_array_mut() {
+        if !servers.iter().any(|x| x.as_str() == Some("ruff")) {
+            servers.push("ruff".into());
+        }
+    }
+    let mut formatter = Table::new();
+    formatter.insert("command".to_owned(), "tire".into());
+    formatter.insert(
+        "args".to_owned(),
+        Value::Array(vec![
+            "fmt".into(),
+            "--stdin-filename".into(),
+            "%{buffer_name}".into(),
+        ]),
+    );
+    python.insert("formatter".to_owned(), Value::Table(formatter));
+
+    *languages != before
+}
+
+/// Checks if the TOML *content* contains comments, which would be lost when rewriting it.
+pub fn has_toml_comments(content: &str) -> bool {
+    let bytes = content.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'#' => return true,
+            quote @ (b'"' | b'\'') => {
+                // Skip the string, which may contain a `#`. Only basic strings have escapes.
+                let triple = [quote; 3];
+                let delimiter: &[u8] = if bytes[index..].starts_with(&triple) {
+                    &triple
+                } else {
+                    &triple[..1]
+                };
+                index += delimiter.len();
+                while index < bytes.len() && !bytes[index..].starts_with(delimiter) {
+                    if quote == b'"' && bytes[index] == b'\\' {
+                        index += 1;
+                    }
+                    index += 1;
+                }
+                index += delimiter.len();
+            }
+            _ => index += 1,
+        }
+    }
+    false
+}
+
+/// Returns the table under *key* in *table*, inserting an empty one if it doesn't exist or isn't
+/// a table.
+fn table_entry<'a>(table: &'a mut Table, key: &str) -> &'a mut Table {
+    let value = table
+        .entry(key)
+        .or_insert_with(|| Value::Table(Table::new()));
+    if !value.is_table() {
+        *value = Value::Table(Table::new());
+    }
+    value.as_table_mut().unwrap()
+}
+
+fn configure_vscode(project_root: &Path, config_file: &str) -> Result<(), String> {
+    let file = project_root.join(".vscode").join("settings.json");
+    let mut settings = match std::fs::read_to_string(&file) {
+        // VS Code allows comments and trailing commas in its settings, which we can't preserve.
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(JsonValue::Object(map)) => map,
+            _ => {
+                return Err(format!(
+                    "{} is not plain JSON (it may contain comments), add these settings manually:\n{}",
+                    file.display(),
+                    serde_json::to_string_pretty(&vscode_settings(config_file)).unwrap()
+                ));
+            }
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Map::new(),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    let changed = merge_json(&mut settings, vscode_settings(config_file));
+    if changed.is_empty() {
+        eprintln!("[tire] {} is up to date", file.display());
+        return Ok(());
+    }
+    std::fs::create_dir_all(file.parent().unwrap()).map_err(|err| err.to_string())?;
+    std::fs::write(
+        &file,
+        serde_json::to_string_pretty(&settings).unwrap() + "\n",
+    )
+    .map_err(|err| err.to_string())?;
+    eprintln!("[tire] Updated {}: {}", file.display(), changed.join(", "));
+    Ok(())
+}
+
+fn configure_helix(project_root: &Path, config_file: &str) -> Result<(), String> {
+    let file = project_root.join(".helix").join("languages.toml");
+    let mut languages: Table = match std::fs::read_to_string(&file) {
+        // Comments can't be preserved when the file is written again.
+        Ok(content) if has_toml_comments(&content) => {
+            let mut settings = Table::new();
+            merge_helix(&mut settings, config_file);
+            return Err(format!(
+                "{} contains comments, add these settings manually:\n{}",
+                file.display(),
+                toml::to_string(&settings).unwrap()
+            ));
+        }
+        Ok(content) => content.parse().map_err(|err| format!("{err}"))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Table::new(),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    if !merge_helix(&mut languages, config_file) {
+        eprintln!("[tire] {} is up to date", file.display());
+        return Ok(());
+    }
+    std::fs::create_dir_all(file.parent().unwrap()).map_err(|err| err.to_string())?;
+    std::fs::write(
+        &file,
+        toml::to_string(&languages).map_err(|err| err.to_string())?,
+    )
+    .map_err(|err| err.to_string())?;
+    eprintln!("[tire] Updated {}", file.display());
+    Ok(())
+}
+
+fn print_pycharm_instructions(config_file: &str) {
+    println!("Configure PyCharm with the following settings:");
+    println!();
+    println!(
+        "  Python Interpreter:  {} (Add Interpreter > Existing)",
+        venv_python()
+    );
+    println!("  Ruff plugin:         Tools > Ruff > Config file: {config_file}");
+    println!("  Mypy plugin:         Tools > Mypy > Arguments: --config-file {config_file}");
+    println!(
+        "  pytest:              Run Configurations > Templates > pytest > Additional Arguments:"
+    );
+    println!("                       -c {config_file} --rootdir .");
+}
+
+pub fn editor(editor: Editor) -> Outcome {
+    // Make sure the materialized configuration exists, as the editor reads it directly.
+    let materialized = Profile::load(None).unwrap().materialize(None).unwrap();
+    let config_file = materialized
+        .file
+        .strip_prefix(&materialized.project_root)
+        .unwrap_or(&materialized.file)
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let result = match editor {
+        Editor::Vscode => configure_vscode(&materialized.project_root, &config_file),
+        Editor::Pycharm => {
+            print_pycharm_instructions(&config_file);
+            Ok(())
+        }
+        Editor::Helix => configure_helix(&materialized.project_root, &config_file),
+    };
+    match result {
+        Ok(()) => Outcome::Success,
+        Err(err) => {
+            eprintln!("[tire] {err}");
+            Outcome::Error
+        }
+    }
+}
@@ -1,68 +1,185 @@
-//! Implements the `tire check` command.
+//! Implements the `tire fmt` command.
+
+use std::io::Read;
+
+use toml::{Table, Value};
 
 use crate::{
-    profile::Profile,
-    utils::{run_command_or_exit, string_vec},
+    profile::{Materialized, Profile},
+    utils::{ExitCodes, Outcome, pipe_command, run_tool, string_vec},
 };
 
-pub fn fmt(files: Vec<String>, check: bool) {
-    // Write the merged configuration to a temporary file
-    let pyproject_toml = Profile::load(None).unwrap().materialize(None).unwrap();
+/// The code of Ruff's rule for unsorted imports.
+const UNSORTED_IMPORTS: &str = "I001";
+
+/// Returns the rule selectors in the Ruff configuration under the given key, looking in both
+/// `[tool.ruff.lint]` and the deprecated top-level `[tool.ruff]` section.
+fn ruff_selectors<'a>(config: &'a Table, key: &str) -> Vec<&'a str> {
+    let Some(ruff) = config.get("tool").and_then(|x| x.get("ruff")) else {
+        return vec![];
+    };
+    [ruff.get("lint").and_then(|x| x.get(key)), ruff.get(key)]
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+/// Checks if a Ruff rule selector such as `I`, `I0` or `ALL` selects the given rule code.
+fn selects(selector: &str, code: &str) -> bool {
+    selector == "ALL" || (!selector.is_empty() && code.starts_with(selector))
+}
 
-    // TODO: Do not fail fast on the commands.
+/// Checks if the merged Ruff configuration enables import sorting, i.e. if it selects the `I`
+/// rules and does not ignore them. Without a `select` setting, Ruff's defaults apply, which do
+/// not include the `I` rules.
+pub fn selects_import_rules(config: &Table) -> bool {
+    let selected = ruff_selectors(config, "select")
+        .into_iter()
+        .chain(ruff_selectors(config, "extend-select"))
+        .any(|x| selects(x, UNSORTED_IMPORTS));
+    let ignored = ruff_selectors(config, "ignore")
+        .into_iter()
+        .any(|x| selects(x, UNSORTED_IMPORTS));
+    selected && !ignored
+}
 
-    // Run dmypy with the merged config file
+/// Checks if `tire fmt` should sort imports. This can be set explicitly with
+/// `[tool.tire.fmt].sort-imports`, and otherwise depends on whether the Ruff configuration selects
+/// the `I` rules.
+fn sort_imports(materialized: &Materialized) -> bool {
+    match materialized
+        .setting(&["fmt", "sort-imports"])
+        .and_then(Value::as_bool)
     {
-        let mut uv_command = string_vec![
-            "uv",
-            "run",
-            "--with",
-            "ruff",
-            "ruff",
-            "format",
-            "--config",
-            pyproject_toml.to_string_lossy().to_string()
-        ];
-
-        if check {
-            uv_command.push("--check".to_owned());
-        }
+        Some(sort_imports) => sort_imports,
+        None => selects_import_rules(&materialized.config),
+    }
+}
+
+/// A step of `tire fmt`, which runs a Ruff command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    /// Sort imports with `ruff check --select I`, which `ruff format` does not do.
+    SortImports,
 
-        if files.is_empty() {
-            uv_command.push(".".to_owned());
-        } else {
-            uv_command.extend(files.clone());
+    /// Format the code with `ruff format`.
+    Format,
+}
+
+impl Step {
+    /// Returns the Ruff command of this step with the given *args*.
+    fn command(self, materialized: &Materialized, args: &[&str]) -> Vec<String> {
+        let mut uv_command = string_vec!["uv", "run", "--with", "ruff", "ruff"];
+        match self {
+            Step::SortImports => uv_command.extend(string_vec![
+                "check",
+                "--config",
+                materialized.file_arg(),
+                "--select",
+                "I"
+            ]),
+            Step::Format => {
+                uv_command.extend(string_vec!["format", "--config", materialized.file_arg()])
+            }
         }
+        uv_command.extend(args.iter().map(|x| x.to_string()));
+        uv_command
+    }
+}
 
-        run_command_or_exit(uv_command);
+/// Returns the steps of `tire fmt` in order. Imports are sorted first, as the formatter may need
+/// to wrap the sorted imports.
+fn steps(materialized: &Materialized) -> Vec<Step> {
+    if sort_imports(materialized) {
+        vec![Step::SortImports, Step::Format]
+    } else {
+        vec![Step::Format]
     }
+}
 
-    // Check isort rules
-    // TODO: Only if the profile includes `select = ["I"]` in the ruff config
-    {
-        let mut uv_command = string_vec![
-            "uv",
-            "run",
-            "--with",
-            "ruff",
-            "ruff",
-            "check",
-            "--config",
-            pyproject_toml.to_string_lossy().to_string(),
-            "--select",
-            "I"
-        ];
-
-        if !check {
-            uv_command.push("--fix".to_owned());
-        }
+/// Format the given files. With *check*, only check whether formatting would change them, and with
+/// *diff*, show what would change instead of writing the files.
+pub fn fmt(files: Vec<String>, check: bool, diff: bool) -> Outcome {
+    // Write the merged configuration to a temporary file
+    let materialized = Profile::load(None).unwrap().materialize(None).unwrap();
+    format_files(&materialized, &files, check, diff)
+}
 
-        if files.is_empty() {
-            uv_command.push(".".to_owned());
-        } else {
-            uv_command.extend(files);
+/// Format the given files (or the current directory) with the *materialized* configuration, see
+/// [fmt].
+pub fn format_files(
+    materialized: &Materialized,
+    files: &[String],
+    check: bool,
+    diff: bool,
+) -> Outcome {
+    let files: Vec<&str> = if files.is_empty() {
+        vec!["."]
+    } else {
+        files.iter().map(String::as_str).collect()
+    };
+
+    // We don't fail fast, so that `--check` reports formatting and import problems together.
+    let mut outcome = Outcome::Success;
+    for step in steps(materialized) {
+        let mut args = match (step, diff, check) {
+            (_, true, _) => vec!["--diff"],
+            (_, false, true) => vec!["--check"],
+            (Step::SortImports, false, false) => vec!["--fix"],
+            (Step::Format, false, false) => vec![],
+        };
+        args.extend(&files);
+        outcome = outcome.max(run_tool(
+            step.command(materialized, &args),
+            &ExitCodes::ruff(),
+        ));
+    }
+    outcome
+}
+
+/// Format the code read from standard input and write it to standard output, e.g. for format on
+/// save in editors. *filename* is used to find the settings that apply to the code, such as
+/// per-file ignores, and doesn't need to exist. Nothing is written if formatting fails, e.g.
+/// because of a syntax error.
+pub fn fmt_stdin(filename: &str) -> Outcome {
+    let materialized = Profile::load(None).unwrap().materialize(None).unwrap();
+
+    let mut input = Vec::new();
+    if let Err(err) = std::io::stdin().read_to_end(&mut input) {
+        eprintln!("[tire] Failed to read from stdin: {err}");
+        return Outcome::Error;
+    }
+    match format_code(&materialized, filename, &String::from_utf8_lossy(&input)) {
+        Ok(code) => {
+            print!("{code}");
+            Outcome::Success
         }
+        Err(outcome) => outcome,
+    }
+}
 
-        run_command_or_exit(uv_command);
+/// Format the given *code* with the *materialized* configuration and return the result, see
+/// [fmt_stdin].
+pub fn format_code(
+    materialized: &Materialized,
+    filename: &str,
+    code: &str,
+) -> Result<String, Outcome> {
+    let mut code = code.to_owned();
+    for step in steps(materialized) {
+        let args = match step {
+            Step::SortImports => vec!["--fix", "--exit-zero", "--stdin-filename", filename, "-"],
+            Step::Format => vec!["--stdin-filename", filename, "-"],
+        };
+        let (status, stdout) = pipe_command(step.command(materialized, &args), code.as_bytes());
+        let outcome = ExitCodes::ruff().outcome(status.code());
+        if outcome != Outcome::Success {
+            return Err(outcome);
+        }
+        code = stdout;
     }
+    Ok(code)
 }
@@ -0,0 +1,223 @@
+//! Implements the `tire hooks` and `tire hook` commands.
+//!
+//! `tire hooks install` installs a Git pre-commit hook that runs `tire hook`, which checks the
+//! formatting of and lints only the staged Python files. Unstaged changes are set aside while the
+//! checks run, so that they check what is actually being committed.
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    fmt::fmt,
+    lint::{LintOptions, lint},
+    select::{Error, GitSelection, git, python_files},
+    utils::{Outcome, find_project_root},
+};
+
+/// Identifies pre-commit hooks installed by Tire, so that we never overwrite or remove others.
+const MARKER: &str = "# Installed by `tire hooks install`.";
+
+/// Returns the contents of the Git pre-commit hook.
+pub fn hook_script() -> String {
+    format!("#!/bin/sh\n{MARKER} Remove with `tire hooks uninstall`.\nexec tire hook\n")
+}
+
+/// Checks if the given hook was installed by Tire.
+pub fn is_tire_hook(content: &str) -> bool {
+    content.lines().any(|x| x.starts_with(MARKER))
+}
+
+/// An entry for a `.pre-commit-config.yaml`. The pre-commit framework sets unstaged changes aside
+/// itself, so `tire hook` doesn't need to.
+pub const PRE_COMMIT_CONFIG: &str = r#"- repo: local
+  hooks:
+    - id: tire
+      name: tire
+      entry: tire hook --no-stash
+      language: system
+      types_or: [python, pyi]
+      pass_filenames: false
+"#;
+
+/// Returns the path of the repository's pre-commit hook, respecting `core.hooksPath`.
+fn pre_commit_hook(project_root: &Path) -> Result<PathBuf, Error> {
+    let hooks_dir = git(project_root, &["rev-parse", "--git-path", "hooks"])?;
+    Ok(project_root.join(hooks_dir.trim()).join("pre-commit"))
+}
+
+pub fn install(pre_commit: bool) -> Outcome {
+    if pre_commit {
+        println!("{PRE_COMMIT_CONFIG}");
+        eprintln!("[tire] Add the above to the `repos` in your `.pre-commit-config.yaml`.");
+        return Outcome::Success;
+    }
+
+    let project_root = find_project_root(None).unwrap();
+    let hook = match pre_commit_hook(&project_root) {
+        Ok(hook) => hook,
+        Err(err) => {
+            eprintln!("[tire] Failed to locate the Git hooks directory: {err}");
+            return Outcome::Error;
+        }
+    };
+    if let Ok(content) = std::fs::read_to_string(&hook) {
+        if !is_tire_hook(&content) {
+            eprintln!(
+                "[tire] {} exists and was not installed by Tire. Remove it first, or use \
+                 `--pre-commit` to integrate with the pre-commit framework.",
+                hook.display()
+            );
+            return Outcome::Failure;
+        }
+    }
+
+    let result = std::fs::create_dir_all(hook.parent().unwrap())
+        .and_then(|_| std::fs::write(&hook, hook_script()))
+        .and_then(|_| make_executable(&hook));
+    match result {
+        Ok(()) => {
+            eprintln!("[tire] Installed {}", hook.display());
+            Outcome::Success
+        }
+        Err(err) => {
+            eprintln!("[tire] Failed to install {}: {err}", hook.display());
+            Outcome::Error
+        }
+    }
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+pub fn uninstall() -> Outcome {
+    let project_root = find_project_root(None).unwrap();
+    let hook = match pre_commit_hook(&project_root) {
+        Ok(hook) => hook,
+        Err(err) => {
+            eprintln!("[tire] Failed to locate the Git hooks directory: {err}");
+            return Outcome::Error;
+        }
+    };
+    match std::fs::read_to_string(&hook) {
+        Ok(content) if is_tire_hook(&content) => {
+            std::fs::remove_file(&hook).unwrap();
+            eprintln!("[tire] Removed {}", hook.display());
+            Outcome::Success
+        }
+        Ok(_) => {
+            eprintln!(
+                "[tire] {} was not installed by Tire, leaving it alone.",
+                hook.display()
+            );
+            Outcome::Failure
+        }
+        Err(_) => {
+            eprintln!("[tire] No pre-commit hook is installed.");
+            Outcome::Success
+        }
+    }
+}
+
+/// Sets aside the unstaged changes of tracked files as a patch, and restores them when dropped.
+pub struct Unstaged {
+    toplevel: PathBuf,
+    patch: PathBuf,
+}
+
+impl Unstaged {
+    /// Set aside the unstaged changes, if there are any. Fails if a patch from an earlier run
+    /// that could not be restored still exists, as it may hold the only copy of the user's work.
+    pub fn set_aside(project_root: &Path) -> Result<Option<Self>, Error> {
+        let toplevel = PathBuf::from(git(project_root, &["rev-parse", "--show-toplevel"])?.trim());
+        let patch = toplevel.join(
+            git(
+                &toplevel,
+                &["rev-parse", "--git-path", "tire-unstaged.patch"],
+            )?
+            .trim(),
+        );
+        if patch.exists() {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} exists, restore it with `git apply` or remove it first",
+                    patch.display()
+                ),
+            )));
+        }
+
+        // Git writes the patch itself, as the diff may contain text that isn't UTF-8.
+        let output = format!("--output={}", patch.to_string_lossy());
+        git(
+            &toplevel,
+            &[
+                "diff",
+                "--binary",
+                "--no-color",
+                "--no-ext-diff",
+                "--ignore-submodules",
+                &output,
+            ],
+        )?;
+        if std::fs::metadata(&patch)?.len() == 0 {
+            std::fs::remove_file(&patch)?;
+            return Ok(None);
+        }
+        git(&toplevel, &["checkout", "--", "."])?;
+        eprintln!("[tire] Unstaged changes set aside in {}", patch.display());
+        Ok(Some(Self { toplevel, patch }))
+    }
+}
+
+impl Drop for Unstaged {
+    fn drop(&mut self) {
+        let patch = self.patch.to_string_lossy();
+        match git(&self.toplevel, &["apply", "--whitespace=nowarn", &patch]) {
+            Ok(_) => {
+                let _ = std::fs::remove_file(&self.patch);
+                eprintln!("[tire] Restored unstaged changes");
+            }
+            Err(err) => eprintln!(
+                "[tire] Failed to restore unstaged changes, they are saved in {patch}: {err}"
+            ),
+        }
+    }
+}
+
+pub fn hook(no_stash: bool) -> Outcome {
+    let cwd = std::env::current_dir().unwrap();
+    let files = match python_files(&cwd, &GitSelection::Staged) {
+        Ok(Some(files)) => files,
+        Ok(None) => return Outcome::Success,
+        Err(err) => {
+            eprintln!("[tire] Failed to select staged files: {err}");
+            return Outcome::Error;
+        }
+    };
+
+    let project_root = find_project_root(None).unwrap();
+    let _unstaged = if no_stash {
+        None
+    } else {
+        match Unstaged::set_aside(&project_root) {
+            Ok(unstaged) => unstaged,
+            Err(err) => {
+                eprintln!("[tire] Failed to set aside unstaged changes: {err}");
+                return Outcome::Error;
+            }
+        }
+    };
+
+    let outcome = fmt(files.clone(), true, false);
+    outcome.max(lint(LintOptions {
+        files,
+        ..Default::default()
+    }))
+}
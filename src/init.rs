@@ -0,0 +1,249 @@
+//! Implements the `tire init` command.
+//!
+//! Scaffolds a Uv project with a `src/` layout, a sample test and a `.gitignore` for Tire's state.
+//! It can be run in an existing directory, in which case only the missing pieces are added and
+//! existing files are never overwritten.
+
+use std::path::{Path, PathBuf};
+
+use toml::Table;
+
+use crate::utils::Outcome;
+
+/// The kind of project to create with `tire init`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProjectKind {
+    /// A library that is meant to be imported by other projects.
+    #[default]
+    Lib,
+
+    /// An application with a command-line entrypoint.
+    App,
+}
+
+/// The lines that `tire init` makes sure are in the `.gitignore`.
+const GITIGNORE: [&str; 6] = [
+    "__pycache__/",
+    ".venv/",
+    "/dist/",
+    ".coverage",
+    ".tire/",
+    ".dmypy.json",
+];
+
+/// The keywords of Python, which can't be used as package names.
+const PYTHON_KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Converts a project name to the name of its Python package, e.g. `my-project` to `my_project`.
+pub fn package_name(project_name: &str) -> String {
+    project_name
+        .trim()
+        .to_lowercase()
+        .replace(['-', '.', ' '], "_")
+}
+
+/// Checks if *name* can be imported as a Python package, i.e. it is an identifier and not a
+/// keyword.
+pub fn is_valid_package_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|x| x.is_alphabetic() || x == '_')
+        && chars.all(|x| x.is_alphanumeric() || x == '_')
+        && !PYTHON_KEYWORDS.contains(&name)
+}
+
+/// Returns the contents of a new `pyproject.toml`.
+pub fn pyproject_toml(project_name: &str, kind: ProjectKind) -> String {
+    let package = package_name(project_name);
+
+    // The name is quoted and escaped as a TOML string, so any name results in a valid file.
+    let project_name = toml::Value::String(project_name.to_owned()).to_string();
+    let mut content = format!(
+        r#"[project]
+name = {project_name}
+version = "0.1.0"
+description = ""
+readme = "README.md"
+requires-python = ">=3.10"
+dependencies = []
+"#
+    );
+    if kind == ProjectKind::App {
+        let entrypoint = toml::Value::String(format!("{package}:main"));
+        content += &format!(
+            r#"
+[project.scripts]
+{project_name} = {entrypoint}
+"#
+        );
+    }
+    content += r#"
+[dependency-groups]
+dev = ["pytest"]
+
+[build-system]
+requires = ["hatchling"]
+build-backend = "hatchling.build"
+
+"#;
+    content += TOOL_TIRE;
+    content
+}
+
+/// The `[tool.tire]` section added to the `pyproject.toml`.
+const TOOL_TIRE: &str = r#"[tool.tire]
+profile = "default"
+"#;
+
+/// Returns the contents of the package's `__init__.py`.
+fn init_py(kind: ProjectKind) -> &'static str {
+    match kind {
+        ProjectKind::Lib => "",
+        ProjectKind::App => "def main() -> None:\n    print(\"Hello, world!\")\n",
+    }
+}
+
+/// Returns the contents of the sample test.
+fn sample_test(package: &str) -> String {
+    format!(
+        r#"import {package}
+
+
+def test_import() -> None:
+    assert {package}.__name__ == "{package}"
+"#
+    )
+}
+
+/// Returns the lines of [GITIGNORE] that are not yet in the given `.gitignore` content.
+pub fn missing_gitignore_lines(content: &str) -> Vec<&'static str> {
+    GITIGNORE
+        .into_iter()
+        .filter(|line| !content.lines().any(|x| x.trim() == *line))
+        .collect()
+}
+
+/// Write *content* to *path* unless it exists already.
+fn create_file(path: &Path, content: &str) -> std::io::Result<()> {
+    if path.exists() {
+        eprintln!("[tire] Keeping existing {}", path.display());
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    eprintln!("[tire] Created {}", path.display());
+    Ok(())
+}
+
+/// Create the project files in *dir* that are missing.
+fn scaffold(dir: &Path, kind: ProjectKind, name: Option<String>) -> std::io::Result<()> {
+    let pyproject_file = dir.join("pyproject.toml");
+    let existing: Option<Table> = match std::fs::read_to_string(&pyproject_file) {
+        Ok(content) => Some(
+            content
+                .parse()
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?,
+        ),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+
+    // Prefer the name of an existing project over the name of the directory.
+    let existing_name = existing
+        .as_ref()
+        .and_then(|x| x.get("project")?.get("name")?.as_str())
+        .map(String::from);
+    let project_name = name
+        .or(existing_name)
+        .or_else(|| dir.file_name().map(|x| x.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "project".to_owned());
+    let package = package_name(&project_name);
+    if !is_valid_package_name(&package) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "{package:?} is not a valid Python package name, choose another name with `--name`"
+            ),
+        ));
+    }
+
+    match &existing {
+        None => create_file(&pyproject_file, &pyproject_toml(&project_name, kind))?,
+        Some(table) if table.get("tool").and_then(|x| x.get("tire")).is_none() => {
+            // Append the section instead of re-serializing, which would lose comments and
+            // formatting.
+            let mut content = std::fs::read_to_string(&pyproject_file)?;
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content += "\n";
+            content += TOOL_TIRE;
+            std::fs::write(&pyproject_file, content)?;
+            eprintln!("[tire] Added [tool.tire] to {}", pyproject_file.display());
+        }
+        Some(_) => eprintln!("[tire] Keeping existing {}", pyproject_file.display()),
+    }
+
+    let package_dir: PathBuf = dir.join("src").join(&package);
+    create_file(&package_dir.join("__init__.py"), init_py(kind))?;
+    create_file(&package_dir.join("py.typed"), "")?;
+
+    let tests_dir = dir.join("tests");
+    if tests_dir.exists() {
+        eprintln!("[tire] Keeping existing {}", tests_dir.display());
+    } else {
+        create_file(
+            &tests_dir.join(format!("test_{package}.py")),
+            &sample_test(&package),
+        )?;
+    }
+
+    create_file(&dir.join("README.md"), &format!("# {project_name}\n"))?;
+
+    let gitignore_file = dir.join(".gitignore");
+    let verb = if gitignore_file.exists() {
+        "Updated"
+    } else {
+        "Created"
+    };
+    let gitignore = match std::fs::read_to_string(&gitignore_file) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let missing = missing_gitignore_lines(&gitignore);
+    if !missing.is_empty() {
+        let mut content = gitignore;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        for line in missing {
+            content += line;
+            content.push('\n');
+        }
+        std::fs::write(&gitignore_file, content)?;
+        eprintln!("[tire] {verb} {}", gitignore_file.display());
+    }
+
+    Ok(())
+}
+
+pub fn init(kind: ProjectKind, name: Option<String>) -> Outcome {
+    let cwd = std::env::current_dir().unwrap();
+    match scaffold(&cwd, kind, name) {
+        Ok(()) => {
+            eprintln!("[tire] Run `uv sync` to create the virtual environment.");
+            Outcome::Success
+        }
+        Err(err) => {
+            eprintln!("[tire] Failed to initialize the project: {err}");
+            Outcome::Error
+        }
+    }
+}
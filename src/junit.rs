@@ -0,0 +1,261 @@
+//! Parses the JUnit XML reports written by `pytest --junit-xml`.
+//!
+//! Tire uses the JUnit report rather than pytest's console output to learn about the outcome of
+//! individual tests, e.g. to print its own summary or to write a machine-readable report.
+
+use std::{fmt::Display, path::Path};
+
+use quick_xml::{
+    Reader,
+    events::{BytesStart, Event},
+};
+use serde::Serialize;
+
+/// Error type for loading a JUnit report.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Xml(#[from] quick_xml::Error),
+
+    #[error(transparent)]
+    Attr(#[from] quick_xml::events::attributes::AttrError),
+}
+
+/// The outcome of a single test.
+///
+/// Outcomes are ordered such that if a test case reports multiple results (e.g. a failure in
+/// the test and an error in its teardown), the greater one wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Passed,
+    /// The test failed, but passed when it was rerun. Never read from a JUnit report, see
+    /// [TestReport::merge_retry].
+    Flaky,
+    Skipped,
+    XFailed,
+    Failed,
+    Error,
+}
+
+/// A single test case from a JUnit report.
+#[derive(Clone, Debug, Serialize)]
+pub struct TestCase {
+    /// The dotted path of the test module and class, e.g. `tests.test_foo.TestFoo`.
+    pub classname: String,
+
+    /// The name of the test function, including parameters, e.g. `test_bar[1]`.
+    pub name: String,
+
+    /// The outcome of the test.
+    pub outcome: TestOutcome,
+
+    /// The duration of the test in seconds.
+    pub duration: f64,
+
+    /// The message of the failure, error or skip, if any.
+    pub message: Option<String>,
+}
+
+impl TestCase {
+    /// Returns an identifier for the test, e.g. `tests.test_foo.TestFoo::test_bar[1]`.
+    pub fn id(&self) -> String {
+        format!("{}::{}", self.classname, self.name)
+    }
+}
+
+/// The test cases of a JUnit report.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TestReport {
+    /// All test cases in the report, in the order they appear in.
+    pub tests: Vec<TestCase>,
+
+    /// The wall time of the test run in seconds, as reported by the test suites.
+    pub duration: f64,
+}
+
+impl TestReport {
+    /// Load a JUnit report from the given file.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse a JUnit report from the given XML string.
+    pub fn parse(xml: &str) -> Result<Self, Error> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+
+        let mut report = TestReport::default();
+        let mut current: Option<TestCase> = None;
+        loop {
+            let (event, is_empty) = match reader.read_event()? {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(e) => {
+                    if e.name().as_ref() == b"testcase" {
+                        report.tests.extend(current.take());
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            match event.name().as_ref() {
+                b"testsuite" => {
+                    if let Some(time) = attribute(&event, "time")? {
+                        report.duration += time.parse::<f64>().unwrap_or(0.0);
+                    }
+                }
+                b"testcase" => {
+                    let case = TestCase {
+                        classname: attribute(&event, "classname")?.unwrap_or_default(),
+                        name: attribute(&event, "name")?.unwrap_or_default(),
+                        outcome: TestOutcome::Passed,
+                        duration: attribute(&event, "time")?
+                            .and_then(|x| x.parse().ok())
+                            .unwrap_or(0.0),
+                        message: None,
+                    };
+                    if is_empty {
+                        report.tests.push(case);
+                    } else {
+                        current = Some(case);
+                    }
+                }
+                tag @ (b"failure" | b"error" | b"skipped") => {
+                    let Some(case) = current.as_mut() else {
+                        continue;
+                    };
+                    let outcome = match tag {
+                        b"failure" => TestOutcome::Failed,
+                        b"error" => TestOutcome::Error,
+                        _ if attribute(&event, "type")?.as_deref() == Some("pytest.xfail") => {
+                            TestOutcome::XFailed
+                        }
+                        _ => TestOutcome::Skipped,
+                    };
+                    if outcome > case.outcome {
+                        case.outcome = outcome;
+                        case.message = attribute(&event, "message")?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Merge the report of a rerun of the failed tests into this report. Failed tests that passed
+    /// in the rerun are marked as [TestOutcome::Flaky], tests that failed again keep their outcome.
+    pub fn merge_retry(&mut self, retry: &TestReport) {
+        for case in &retry.tests {
+            if case.outcome != TestOutcome::Passed {
+                continue;
+            }
+            let id = case.id();
+            if let Some(test) = self.tests.iter_mut().find(|x| {
+                matches!(x.outcome, TestOutcome::Failed | TestOutcome::Error) && x.id() == id
+            }) {
+                test.outcome = TestOutcome::Flaky;
+            }
+        }
+        self.duration += retry.duration;
+    }
+
+    /// Returns the IDs of the tests that are failing, i.e. that failed or errored.
+    pub fn failing(&self) -> Vec<String> {
+        self.tests
+            .iter()
+            .filter(|x| matches!(x.outcome, TestOutcome::Failed | TestOutcome::Error))
+            .map(TestCase::id)
+            .collect()
+    }
+
+    /// Returns the IDs of the tests that passed on a retry.
+    pub fn flaky(&self) -> Vec<String> {
+        self.tests
+            .iter()
+            .filter(|x| x.outcome == TestOutcome::Flaky)
+            .map(TestCase::id)
+            .collect()
+    }
+
+    /// Returns the number of tests per outcome.
+    pub fn summary(&self) -> Summary {
+        let mut summary = Summary {
+            duration: self.duration,
+            ..Default::default()
+        };
+        for test in &self.tests {
+            match test.outcome {
+                TestOutcome::Passed => summary.passed += 1,
+                TestOutcome::Flaky => summary.flaky += 1,
+                TestOutcome::Skipped => summary.skipped += 1,
+                TestOutcome::XFailed => summary.xfailed += 1,
+                TestOutcome::Failed => summary.failed += 1,
+                TestOutcome::Error => summary.errors += 1,
+            }
+        }
+        summary
+    }
+
+    /// Returns up to *n* tests, ordered by their duration in descending order.
+    pub fn slowest(&self, n: usize) -> Vec<&TestCase> {
+        let mut tests: Vec<&TestCase> = self.tests.iter().collect();
+        tests.sort_by(|a, b| b.duration.total_cmp(&a.duration));
+        tests.truncate(n);
+        tests
+    }
+}
+
+/// The number of tests per outcome in a [TestReport].
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub passed: usize,
+    pub flaky: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub skipped: usize,
+    pub xfailed: usize,
+    pub duration: f64,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = [
+            (self.passed, "passed"),
+            (self.flaky, "flaky"),
+            (self.failed, "failed"),
+            (
+                self.errors,
+                if self.errors == 1 { "error" } else { "errors" },
+            ),
+            (self.skipped, "skipped"),
+            (self.xfailed, "xfailed"),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, label)| format!("{count} {label}"))
+            .collect();
+        if parts.is_empty() {
+            write!(f, "no tests ran")?;
+        } else {
+            write!(f, "{}", parts.join(", "))?;
+        }
+        write!(f, " in {:.2}s", self.duration)
+    }
+}
+
+/// Returns the unescaped value of the attribute *name* of the given element.
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, Error> {
+    match element.try_get_attribute(name)? {
+        Some(attr) => Ok(Some(attr.unescape_value()?.into_owned())),
+        None => Ok(None),
+    }
+}
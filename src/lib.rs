@@ -1,8 +1,21 @@
 pub mod add;
+pub mod baseline;
+pub mod cache;
 pub mod check;
+pub mod ci;
+pub mod clean;
+pub mod diagnostic;
+pub mod doctor;
+pub mod editor;
 pub mod fmt;
+pub mod hooks;
+pub mod init;
+pub mod junit;
 pub mod lint;
 pub mod profile;
 pub mod run;
+pub mod select;
+pub mod shard;
 pub mod test;
 pub mod utils;
+pub mod watch;
@@ -1,15 +1,48 @@
-//! Implements the `tire check` command.
+//! Implements the `tire lint` command.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, IsTerminal, Write},
+};
 
 use crate::{
-    profile::Profile,
-    utils::{run_command_or_exit, string_vec},
+    baseline::{self, baseline_file},
+    cache::{Cache, report_cached},
+    diagnostic::{Diagnostic, OutputFormat, parse_ruff_json},
+    profile::{Materialized, Profile},
+    utils::{ExitCodes, Outcome, capture_command, run_tool, string_vec},
 };
 
-pub fn lint(files: Vec<String>, fix: bool, unsafe_fixes: bool) {
-    // Write the merged configuration to a temporary file
-    let pyproject_toml = Profile::load(None).unwrap().materialize(None).unwrap();
+/// Options for the `tire lint` command.
+#[derive(Clone, Debug, Default)]
+pub struct LintOptions {
+    /// Files or directories to lint recursively. Defaults to the current working directory.
+    pub files: Vec<String>,
+
+    /// Automatically fix applicable lints.
+    pub fix: bool,
+
+    /// Enable potentially unsafe fixes.
+    pub unsafe_fixes: bool,
+
+    /// Review the fixes of each file before applying them.
+    pub interactive: bool,
+
+    /// Summarize which rules were fixed.
+    pub report: bool,
+
+    /// The format in which to report lint violations.
+    pub format: OutputFormat,
 
-    // Run dmypy with the merged config file
+    /// Record the lint violations in the baseline instead of reporting them.
+    pub update_baseline: bool,
+
+    /// Run Ruff even if nothing changed since it last succeeded.
+    pub no_cache: bool,
+}
+
+/// Build a `ruff check` command with the merged configuration and the given arguments.
+fn ruff_command(materialized: &Materialized, args: &[&str]) -> Vec<String> {
     let mut uv_command = string_vec![
         "uv",
         "run",
@@ -18,23 +51,225 @@ pub fn lint(files: Vec<String>, fix: bool, unsafe_fixes: bool) {
         "ruff",
         "check",
         "--config",
-        pyproject_toml.to_string_lossy().to_string()
+        materialized.file_arg()
     ];
+    uv_command.extend(args.iter().map(|x| x.to_string()));
+    uv_command
+}
 
-    if fix {
-        uv_command.push("--fix".to_owned());
-        if unsafe_fixes {
-            uv_command.push("--unsafe-fixes".to_owned());
+/// Run `ruff check` with the given arguments and parse its diagnostics.
+fn ruff_diagnostics(
+    materialized: &Materialized,
+    args: &[&str],
+) -> Result<Vec<Diagnostic>, Outcome> {
+    let mut uv_command = ruff_command(materialized, &["--output-format", "json"]);
+    uv_command.extend(args.iter().map(|x| x.to_string()));
+    let (status, stdout) = capture_command(uv_command);
+    if ExitCodes::ruff().outcome(status.code()) == Outcome::Error {
+        print!("{stdout}");
+        return Err(Outcome::Error);
+    }
+    parse_ruff_json(&stdout).map_err(|err| {
+        eprintln!("[tire] Failed to parse the output of `ruff`: {err}");
+        Outcome::Error
+    })
+}
+
+/// Splits the unified diff produced by `ruff check --diff` into the diffs of the individual files.
+pub fn split_diff(diff: &str) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = Vec::new();
+    let mut lines = diff.lines().peekable();
+    while let Some(line) = lines.next() {
+        let is_header =
+            line.starts_with("--- ") && lines.peek().is_some_and(|next| next.starts_with("+++ "));
+        if is_header {
+            let file = line[4..].trim().to_owned();
+            files.push((file, String::new()));
+        }
+        if let Some((_, content)) = files.last_mut() {
+            content.push_str(line);
+            content.push('\n');
         }
     }
-    // TODO: Error if unsafe_fixes is set but not fix?
+    for (_, content) in &mut files {
+        let trimmed = content.trim_end().len();
+        content.truncate(trimmed);
+        content.push('\n');
+    }
+    files
+}
+
+/// Counts the diagnostics per rule code that are gone in *after* compared to *before*.
+pub fn fixed_rules(before: &[Diagnostic], after: &[Diagnostic]) -> BTreeMap<String, usize> {
+    let mut counts: BTreeMap<String, isize> = BTreeMap::new();
+    for diagnostic in before {
+        *counts
+            .entry(diagnostic.code_or_unknown().to_owned())
+            .or_default() += 1;
+    }
+    for diagnostic in after {
+        *counts
+            .entry(diagnostic.code_or_unknown().to_owned())
+            .or_default() -= 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(code, count)| (code, count as usize))
+        .collect()
+}
+
+/// Show the fixes of each file and ask the user whether to apply them. Returns the files whose
+/// fixes were accepted.
+fn review_fixes(
+    materialized: &Materialized,
+    unsafe_fixes: bool,
+    files: &[String],
+) -> Result<Vec<String>, Outcome> {
+    if !std::io::stdin().is_terminal() {
+        eprintln!("[tire] `--interactive` requires an interactive terminal.");
+        return Err(Outcome::Error);
+    }
 
-    if files.is_empty() {
-        uv_command.push(".".to_owned());
+    let mut uv_command = ruff_command(materialized, &["--diff"]);
+    if unsafe_fixes {
+        uv_command.push("--unsafe-fixes".to_owned());
+    }
+    uv_command.extend(files.iter().cloned());
+    let (status, stdout) = capture_command(uv_command);
+    if ExitCodes::ruff().outcome(status.code()) == Outcome::Error {
+        return Err(Outcome::Error);
+    }
+
+    let mut accepted = Vec::new();
+    let mut accept_all = false;
+    for (file, diff) in split_diff(&stdout) {
+        if !accept_all {
+            print!("{diff}");
+            std::io::stdout().flush().unwrap();
+            eprint!("[tire] Apply these fixes to {file}? [y]es, [n]o, [a]ll, [q]uit: ");
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer).unwrap();
+            match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => {}
+                "a" | "all" => accept_all = true,
+                "q" | "quit" => break,
+                _ => continue,
+            }
+        }
+        accepted.push(file);
+    }
+    Ok(accepted)
+}
+
+pub fn lint(options: LintOptions) -> Outcome {
+    // Write the merged configuration to a temporary file
+    let materialized = Profile::load(None).unwrap().materialize(None).unwrap();
+
+    let files = if options.files.is_empty() {
+        string_vec!["."]
+    } else {
+        options.files.clone()
+    };
+
+    // Reviewing or reporting fixes and updating the baseline are only useful if Ruff runs.
+    let cache =
+        if options.no_cache || options.interactive || options.report || options.update_baseline {
+            None
+        } else {
+            Cache::new(&materialized, "ruff")
+        };
+    if let Some(cache) = &cache {
+        if cache.contains(&cache.key(&files)) {
+            return report_cached("ruff", options.format);
+        }
+    }
+
+    let outcome = lint_files(&options, &materialized, &files);
+    if outcome == Outcome::Success {
+        // Fixes may have changed the files, so the key is computed again.
+        if let Some(cache) = &cache {
+            cache.insert(&cache.key(&files));
+        }
+    }
+    outcome
+}
+
+fn lint_files(options: &LintOptions, materialized: &Materialized, files: &[String]) -> Outcome {
+    let file_args: Vec<&str> = files.iter().map(String::as_str).collect();
+
+    // Remember the violations before fixing them, to report what was fixed.
+    let before = if options.report {
+        match ruff_diagnostics(materialized, &file_args) {
+            Ok(diagnostics) => Some(diagnostics),
+            Err(outcome) => return outcome,
+        }
     } else {
-        uv_command.extend(files);
+        None
+    };
+
+    // In interactive mode, the accepted fixes are applied up front and the lint run that follows
+    // only reports the remaining violations.
+    let mut fix = options.fix;
+    if options.interactive {
+        let accepted = match review_fixes(materialized, options.unsafe_fixes, files) {
+            Ok(accepted) => accepted,
+            Err(outcome) => return outcome,
+        };
+        if !accepted.is_empty() {
+            let mut uv_command = ruff_command(materialized, &["--fix-only"]);
+            if options.unsafe_fixes {
+                uv_command.push("--unsafe-fixes".to_owned());
+            }
+            uv_command.extend(accepted);
+            let outcome = run_tool(uv_command, &ExitCodes::ruff());
+            if outcome != Outcome::Success {
+                return outcome;
+            }
+        }
+        fix = false;
+    }
+
+    // Tire needs to parse the diagnostics to render them in another format, to compare them
+    // against the baseline, or to report which rules were fixed.
+    let structured = options.format != OutputFormat::Text
+        || options.update_baseline
+        || options.report
+        || baseline_file(materialized).exists();
+
+    // Run ruff with the merged config file
+    let mut args = Vec::new();
+    if fix {
+        args.push("--fix");
+        if options.unsafe_fixes {
+            args.push("--unsafe-fixes");
+        }
+    }
+    args.extend(file_args);
+
+    if !structured {
+        return run_tool(ruff_command(materialized, &args), &ExitCodes::ruff());
     }
 
-    // Run the command
-    run_command_or_exit(uv_command);
+    // Convert Ruff's diagnostics into the requested format, leaving out those in the baseline.
+    let diagnostics = match ruff_diagnostics(materialized, &args) {
+        Ok(diagnostics) => diagnostics,
+        Err(outcome) => return outcome,
+    };
+    if let Some(before) = before {
+        let fixed = fixed_rules(&before, &diagnostics);
+        let total: usize = fixed.values().sum();
+        eprintln!("[tire] Fixed {total} violation(s).");
+        for (code, count) in fixed {
+            eprintln!("[tire]   {code:<8} {count}");
+        }
+    }
+    baseline::report(
+        "ruff",
+        diagnostics,
+        files,
+        options.format,
+        options.update_baseline,
+        materialized,
+    )
 }
@@ -1,4 +1,16 @@
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
+use tire::{
+    diagnostic::OutputFormat,
+    editor::Editor,
+    init::ProjectKind,
+    lint::LintOptions,
+    select::{GitSelection, resolve_files, test_files},
+    shard::Shard,
+    test::{ReportFormat, TestOptions},
+    utils::Outcome,
+};
 
 #[derive(Parser)]
 pub struct Args {
@@ -6,6 +18,52 @@ pub struct Args {
     pub cmd: Cmd,
 }
 
+/// Options to select the files to operate on from Git, instead of passing them explicitly.
+#[derive(clap::Args)]
+pub struct Selection {
+    /// Only operate on Python files with uncommitted changes (staged or not) and untracked files.
+    #[arg(long, conflicts_with_all = ["file", "since"])]
+    changed: bool,
+
+    /// Only operate on Python files changed since the merge base with the given Git ref (e.g.
+    /// `origin/main`), including uncommitted changes and untracked files.
+    #[arg(long, value_name = "GIT_REF", conflicts_with = "file")]
+    since: Option<String>,
+}
+
+impl Selection {
+    fn git_selection(&self) -> Option<GitSelection> {
+        match (&self.since, self.changed) {
+            (Some(git_ref), _) => Some(GitSelection::Since(git_ref.clone())),
+            (None, true) => Some(GitSelection::Changed),
+            (None, false) => None,
+        }
+    }
+
+    /// Returns the *files* to operate on, replacing them with the Python files selected from Git
+    /// if requested. Returns [Err] with the outcome to exit with if no files are selected.
+    fn files(&self, files: Vec<String>) -> Result<Vec<String>, Outcome> {
+        resolve_files(
+            files,
+            self.git_selection().as_ref(),
+            tire::select::python_files,
+        )
+    }
+}
+
+#[derive(Subcommand)]
+pub enum HooksAction {
+    /// Install a pre-commit hook that runs `tire hook`.
+    Install {
+        /// Print an entry for a `.pre-commit-config.yaml` instead of installing a Git hook.
+        #[arg(long)]
+        pre_commit: bool,
+    },
+
+    /// Remove the pre-commit hook installed by `tire hooks install`.
+    Uninstall,
+}
+
 #[derive(Subcommand)]
 #[clap(verbatim_doc_comment)]
 pub enum Cmd {
@@ -32,6 +90,7 @@ pub enum Cmd {
     /// This command is analogous to the `uv run` command, but provides a bit more flexibility and
     /// shorter syntax. This command is parsed such that all options before the first positional
     /// argument are passed to Uv, and all subsequent arguments are passed to the call target.
+    /// Use `--` to mark the end of the options for Uv explicitly.
     ///
     /// Examples:
     /// {n}
@@ -43,7 +102,8 @@ pub enum Cmd {
     ///
     /// Differences to Uv:
     /// {n}
-    /// - The `module:func` version runs the function with the `cyclopts` CLI framework.{n}
+    /// - The `module:func` version runs the function with the `cyclopts` CLI framework, or the
+    ///   adapter configured with `[tool.tire.run].adapter`.{n}
     /// - The `@pkg` version runs as `--with pkg pkg`.
     ///
     /// To see which additional arguments you can pass to `tire run` before the first positional
@@ -56,11 +116,83 @@ pub enum Cmd {
     },
 
     /// Type-check your code.
+    ///
+    /// Uses the `dmypy` daemon, which is kept running in the background to speed up subsequent
+    /// runs. The daemon is restarted automatically when the configuration changes.
     Check {
         /// Files or directories to type-check recursively. Defaults to the current working
         /// directory.
         #[arg(name = "file")]
         files: Vec<String>,
+
+        #[command(flatten)]
+        selection: Selection,
+
+        /// Show the status of the dmypy daemon.
+        #[arg(long, conflicts_with_all = ["stop", "restart"])]
+        status: bool,
+
+        /// Stop the dmypy daemon.
+        #[arg(long, conflicts_with = "restart")]
+        stop: bool,
+
+        /// Restart the dmypy daemon before type-checking.
+        #[arg(long)]
+        restart: bool,
+
+        /// The format in which to report type errors. All formats except `text` are rendered by Tire
+        /// from a common representation of the diagnostics.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Record the current type errors in the baseline file, such that only new ones are
+        /// reported by subsequent runs.
+        #[arg(long, conflicts_with_all = ["file", "changed", "since", "status", "stop", "watch"])]
+        update_baseline: bool,
+
+        /// Run mypy even if nothing changed since it last succeeded. Successful runs are cached in
+        /// the `[tool.tire].cache-dir` directory, or `$TIRE_CACHE_DIR`.
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Type-check again whenever Python files in the project change.
+        #[arg(long, conflicts_with_all = ["status", "stop"])]
+        watch: bool,
+    },
+
+    /// Run the steps of a CI pipeline: check the formatting, lint, type-check and test.
+    ///
+    /// All steps run even if one fails, and Tire exits with the most severe outcome. With `-j`,
+    /// independent steps run concurrently and the output of each step is printed in one piece
+    /// when it completes. Output is grouped per step with the log group markers of GitHub Actions
+    /// or GitLab CI when running there.
+    Ci {
+        /// Number of steps to run concurrently.
+        #[arg(long, short = 'j', default_value_t = 1)]
+        jobs: usize,
+    },
+
+    /// Remove Tire's state from the project.
+    ///
+    /// Stops the dmypy daemon and removes the `.tire/` directory.
+    Clean,
+
+    /// Diagnose problems with the environment and the project.
+    ///
+    /// Checks that `uv`, Python and the tools are available, that the project and its profile can
+    /// be loaded, and that Tire's state and the dmypy daemon are healthy. Each check is reported
+    /// with a suggested fix if it does not pass.
+    Doctor,
+
+    /// Configure your editor to use Tire's configuration.
+    ///
+    /// Points the editor's Python interpreter at the project's virtual environment and its Ruff,
+    /// mypy and pytest integrations at the materialized configuration, merging with existing
+    /// settings.
+    Editor {
+        /// The editor to configure.
+        #[arg(value_enum)]
+        editor: Editor,
     },
 
     /// Format your code.
@@ -69,9 +201,56 @@ pub enum Cmd {
         #[arg(name = "file")]
         files: Vec<String>,
 
+        #[command(flatten)]
+        selection: Selection,
+
         /// Only check whether formatting would modify any files.
         #[arg(long)]
         check: bool,
+
+        /// Show a diff of the changes that formatting would make, without modifying any files.
+        #[arg(long)]
+        diff: bool,
+
+        /// Format code from stdin and write it to stdout, applying the settings for the given
+        /// file name. Used by editors to format on save.
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["file", "changed", "since", "check", "diff"])]
+        stdin_filename: Option<String>,
+    },
+
+    /// Check the formatting of and lint the staged Python files.
+    ///
+    /// This is what the pre-commit hook installed by `tire hooks install` runs. Unstaged changes
+    /// are set aside while the checks run and restored afterwards.
+    Hook {
+        /// Do not set aside unstaged changes, e.g. because the pre-commit framework already does.
+        #[arg(long)]
+        no_stash: bool,
+    },
+
+    /// Manage the Git pre-commit hook.
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+
+    /// Create a new project, or add what's missing to an existing one.
+    ///
+    /// Creates a `pyproject.toml` that uses Tire, a `src/` layout with a typed package, a sample
+    /// test and a `.gitignore`. Existing files are never overwritten.
+    Init {
+        /// Create a library (the default).
+        #[arg(long, conflicts_with = "app")]
+        lib: bool,
+
+        /// Create an application with a command-line entrypoint.
+        #[arg(long)]
+        app: bool,
+
+        /// The name of the project. Defaults to the name of an existing project, or the name of
+        /// the current directory.
+        #[arg(long)]
+        name: Option<String>,
     },
 
     /// Lint your code.
@@ -80,13 +259,43 @@ pub enum Cmd {
         #[arg(name = "file")]
         files: Vec<String>,
 
+        #[command(flatten)]
+        selection: Selection,
+
         /// Automatically fix applicable lints.
         #[arg(long)]
         fix: bool,
 
         /// Enable potentially unsafe fixes.
-        #[arg(long)]
+        #[arg(long, requires = "fix")]
         unsafe_fixes: bool,
+
+        /// Show the fixes for each file as a diff and ask whether to apply them.
+        #[arg(long, requires = "fix", conflicts_with = "watch")]
+        interactive: bool,
+
+        /// Summarize which rules were fixed.
+        #[arg(long, requires = "fix")]
+        report: bool,
+
+        /// The format in which to report lint violations. All formats except `text` are rendered by Tire
+        /// from a common representation of the diagnostics.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Record the current lint violations in the baseline file, such that only new ones are
+        /// reported by subsequent runs.
+        #[arg(long, conflicts_with_all = ["file", "changed", "since", "watch"])]
+        update_baseline: bool,
+
+        /// Run Ruff even if nothing changed since it last succeeded. Successful runs are cached in
+        /// the `[tool.tire].cache-dir` directory, or `$TIRE_CACHE_DIR`.
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Lint again whenever Python files in the project change.
+        #[arg(long)]
+        watch: bool,
     },
 
     /// Run tests.
@@ -100,7 +309,11 @@ pub enum Cmd {
         #[arg(name = "file")]
         files: Vec<String>,
 
-        /// Do not error when no tests are discovered.
+        #[command(flatten)]
+        selection: Selection,
+
+        /// Do not error when no tests are discovered. Can also be enabled with the
+        /// `[tool.tire.test].allow-no-tests` setting.
         #[arg(long)]
         allow_no_tests: bool,
 
@@ -115,6 +328,55 @@ pub enum Cmd {
         /// Disable running Python doctests.
         #[arg(long, default_value_t = false)]
         no_doctests: bool,
+
+        /// Disable running doctests in documentation files. By default, doctests are run in
+        /// `README.md`, `docs/**/*.md` and `*.rst` files if they exist. The globs can be
+        /// configured with the `[tool.tire.test].doctest-globs` setting.
+        #[arg(long, default_value_t = false)]
+        no_doctest_globs: bool,
+
+        /// Measure code coverage with `pytest-cov`. Coverage is configured in the
+        /// `[tool.coverage]` section of your `pyproject.toml` or profile. Reports are written in
+        /// HTML, XML (Cobertura) and LCOV format.
+        #[arg(long)]
+        coverage: bool,
+
+        /// The directory to write coverage reports to. Defaults to the
+        /// `[tool.tire.test].coverage-dir` setting, or `coverage/` in the project root.
+        #[arg(long, requires = "coverage")]
+        coverage_dir: Option<PathBuf>,
+
+        /// Write a JUnit XML report of the test results to the given path.
+        #[arg(long)]
+        junit_xml: Option<PathBuf>,
+
+        /// Write a machine-readable report with the outcome and duration of every test.
+        #[arg(long, value_enum)]
+        report: Option<ReportFormat>,
+
+        /// The file to write the `--report` to. Defaults to `.tire/test-report.json`.
+        #[arg(long, requires = "report")]
+        report_file: Option<PathBuf>,
+
+        /// Run only one shard of the test suite, e.g. `2/5` for the second of five shards. Test
+        /// files are partitioned deterministically, balancing their historical duration (or the
+        /// number of tests, with `[tool.tire.test].shard-strategy = "file"`).
+        #[arg(long, value_name = "INDEX/COUNT")]
+        shard: Option<Shard>,
+
+        /// Run only the tests that failed in the last run.
+        #[arg(long)]
+        failed: bool,
+
+        /// Rerun failing tests up to this many times. Tests that pass on a retry are reported as
+        /// flaky. Defaults to the `[tool.tire.test].retries` setting.
+        #[arg(long, value_name = "N")]
+        retries: Option<u32>,
+
+        /// Run the tests again whenever Python files in the project change. The test files
+        /// affected by a change run first, followed by the remaining tests if they pass.
+        #[arg(long)]
+        watch: bool,
     },
 }
 
@@ -124,18 +386,119 @@ fn main() {
         Cmd::Add { args: pkgs, auto } => {
             tire::add::add(pkgs, auto);
         }
-        Cmd::Check { files } => {
-            tire::check::check(files);
+        Cmd::Check {
+            files,
+            status,
+            stop,
+            restart,
+            format,
+            update_baseline,
+            no_cache,
+            watch,
+            selection,
+        } => {
+            // In watch mode, the files are selected again before every run, as they change.
+            if watch {
+                let mut restart = restart;
+                tire::watch::watch(|_| {
+                    let files = match selection.files(files.clone()) {
+                        Ok(files) => files,
+                        Err(outcome) => return outcome,
+                    };
+                    let outcome =
+                        tire::check::check(files, false, false, restart, format, false, no_cache);
+                    restart = false;
+                    outcome
+                });
+            }
+            let files = selection.files(files).unwrap_or_else(|x| x.exit());
+            tire::check::check(
+                files,
+                status,
+                stop,
+                restart,
+                format,
+                update_baseline,
+                no_cache,
+            )
+            .exit();
+        }
+        Cmd::Ci { jobs } => {
+            tire::ci::ci(jobs).exit();
+        }
+        Cmd::Clean => {
+            tire::clean::clean();
+        }
+        Cmd::Doctor => {
+            tire::doctor::doctor().exit();
+        }
+        Cmd::Editor { editor } => {
+            tire::editor::editor(editor).exit();
+        }
+        Cmd::Fmt {
+            files,
+            selection,
+            check,
+            diff,
+            stdin_filename,
+        } => {
+            if let Some(filename) = stdin_filename {
+                tire::fmt::fmt_stdin(&filename).exit();
+            }
+            let files = selection.files(files).unwrap_or_else(|x| x.exit());
+            tire::fmt::fmt(files, check, diff).exit();
+        }
+        Cmd::Hook { no_stash } => {
+            tire::hooks::hook(no_stash).exit();
         }
-        Cmd::Fmt { files, check } => {
-            tire::fmt::fmt(files, check);
+        Cmd::Hooks { action } => match action {
+            HooksAction::Install { pre_commit } => tire::hooks::install(pre_commit).exit(),
+            HooksAction::Uninstall => tire::hooks::uninstall().exit(),
+        },
+        Cmd::Init { lib: _, app, name } => {
+            let kind = if app {
+                ProjectKind::App
+            } else {
+                ProjectKind::Lib
+            };
+            tire::init::init(kind, name).exit();
         }
         Cmd::Lint {
             files,
             fix,
             unsafe_fixes,
+            interactive,
+            report,
+            format,
+            update_baseline,
+            no_cache,
+            watch,
+            selection,
         } => {
-            tire::lint::lint(files, fix, unsafe_fixes);
+            let options = LintOptions {
+                files,
+                fix,
+                unsafe_fixes,
+                interactive,
+                report,
+                format,
+                update_baseline,
+                no_cache,
+            };
+            // In watch mode, the files are selected again before every run, as they change.
+            if watch {
+                tire::watch::watch(|_| match selection.files(options.files.clone()) {
+                    Ok(files) => tire::lint::lint(LintOptions {
+                        files,
+                        ..options.clone()
+                    }),
+                    Err(outcome) => outcome,
+                });
+            }
+            let files = selection
+                .files(options.files.clone())
+                .unwrap_or_else(|x| x.exit());
+            tire::lint::lint(LintOptions { files, ..options }).exit();
         }
         Cmd::Run { args } => {
             tire::run::run(args);
@@ -146,8 +509,42 @@ fn main() {
             parallel,
             filter,
             no_doctests,
+            no_doctest_globs,
+            coverage,
+            coverage_dir,
+            junit_xml,
+            report,
+            report_file,
+            shard,
+            failed,
+            retries,
+            watch,
+            selection,
         } => {
-            tire::test::test(files, allow_no_tests, parallel, filter, !no_doctests);
+            let options = TestOptions {
+                files,
+                allow_no_tests,
+                parallel,
+                filter,
+                doctests: !no_doctests,
+                doctest_globs: !no_doctest_globs,
+                coverage,
+                coverage_dir,
+                junit_xml,
+                report,
+                report_file,
+                shard,
+                failed,
+                retries,
+                ..Default::default()
+            };
+            let selection = selection.git_selection();
+            if watch {
+                tire::test::watch(options, selection);
+            }
+            let files = resolve_files(options.files.clone(), selection.as_ref(), test_files)
+                .unwrap_or_else(|x| x.exit());
+            tire::test::test(TestOptions { files, ..options }).exit();
         }
     }
 }
@@ -13,13 +13,146 @@ use std::path::{Path, PathBuf};
 
 use toml::value::*;
 
-use crate::utils::find_pyproject_toml;
+use crate::utils::{
+    find_project_root, find_pyproject_toml, normalize_path, sha256_hex, write_if_changed,
+};
 
 /// The default profile configuration that comes with Tire.
 const DEFAULT_PROFILE: &str = include_str!("../profiles/default.toml");
 
 /// This contains the names of all well-known `[tool.*]` sections for tools that Tire supports.
-const KNOWN_TOOLS: [&str; 3] = ["mypy", "pytest", "ruff"];
+const KNOWN_TOOLS: [&str; 5] = ["coverage", "mypy", "pytest", "ruff", "tire"];
+
+/// The environment variable that moves Tire's state out of the project, see [state_dir].
+pub const STATE_DIR_ENV: &str = "TIRE_STATE_DIR";
+
+/// Keys in the merged configuration that hold paths, which need to be rebased when the
+/// configuration is materialized outside the project. Glob patterns such as Ruff's `exclude` are
+/// deliberately not included, as prefixing them with a directory changes what they match.
+const PATH_KEYS: [&[&str]; 7] = [
+    &["tool", "coverage", "run", "data_file"],
+    &["tool", "mypy", "cache_dir"],
+    &["tool", "mypy", "files"],
+    &["tool", "mypy", "mypy_path"],
+    &["tool", "ruff", "cache-dir"],
+    &["tool", "ruff", "extend"],
+    &["tool", "ruff", "src"],
+];
+
+/// Returns the directory in which Tire keeps its per-project state, such as the materialized
+/// configuration file.
+///
+/// This is `.tire/` in the project root, unless a base directory is configured with the
+/// [`TIRE_STATE_DIR`][STATE_DIR_ENV] environment variable or the `[tool.tire].state-dir` setting in
+/// the project's `pyproject.toml`, in which case it is a per-project subdirectory of it (see
+/// [external_state_dir]).
+pub fn state_dir(project_root: &Path) -> PathBuf {
+    match configured_state_dir(project_root) {
+        Some(base) => external_state_dir(&base, project_root),
+        None => project_root.join(".tire"),
+    }
+}
+
+/// Returns the base directory for Tire's state configured with the [STATE_DIR_ENV] environment
+/// variable, which takes precedence, or the `[tool.tire].state-dir` setting. Relative paths are
+/// relative to the project root, so that Tire uses the same state in every subdirectory of the
+/// project, and a leading `~/` refers to the home directory.
+fn configured_state_dir(project_root: &Path) -> Option<PathBuf> {
+    let value = match std::env::var(STATE_DIR_ENV) {
+        Ok(value) if !value.is_empty() => value,
+        _ => {
+            let content = std::fs::read_to_string(project_root.join("pyproject.toml")).ok()?;
+            let table: Table = content.parse().ok()?;
+            table
+                .get("tool")?
+                .get("tire")?
+                .get("state-dir")?
+                .as_str()?
+                .to_owned()
+        }
+    };
+    let path = match value.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(std::env::var_os("HOME")?).join(rest),
+        None => PathBuf::from(value),
+    };
+    Some(normalize_path(&project_root.join(path)))
+}
+
+/// Returns the state directory of the project under the given *base* directory, which is keyed by
+/// a hash of the project path so that multiple projects can share the same base directory.
+pub fn external_state_dir(base: &Path, project_root: &Path) -> PathBuf {
+    let project_root = project_root
+        .canonicalize()
+        .unwrap_or_else(|_| project_root.to_path_buf());
+    let name = project_root
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let hash = sha256_hex(project_root.to_string_lossy().as_bytes());
+    base.join(format!("{name}-{}", &hash[..16]))
+}
+
+/// Makes the relative paths in the [PATH_KEYS] of *config* absolute, by resolving them against
+/// the project root. This is needed when the configuration is materialized outside the project.
+pub fn rebase_paths(config: &mut Table, project_root: &Path) {
+    let rebase = |path: &str| {
+        if Path::new(path).is_relative() {
+            project_root.join(path).to_string_lossy().to_string()
+        } else {
+            path.to_owned()
+        }
+    };
+    for key in PATH_KEYS {
+        // Mypy also accepts a comma-separated string of paths for these keys.
+        let comma_separated = matches!(key, ["tool", "mypy", "files" | "mypy_path"]);
+        let rebase_value = |value: &mut Value| {
+            if let Value::String(path) = value {
+                *path = if comma_separated {
+                    path.split(',')
+                        .map(|x| rebase(x.trim()))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                } else {
+                    rebase(path)
+                };
+            }
+        };
+        match value_mut(config, key) {
+            Some(Value::Array(values)) => values.iter_mut().for_each(rebase_value),
+            Some(value) => rebase_value(value),
+            None => {}
+        }
+    }
+}
+
+/// Returns the value at the given *path* of keys in the nested *table*.
+fn value_mut<'a>(table: &'a mut Table, path: &[&str]) -> Option<&'a mut Value> {
+    let (first, rest) = path.split_first()?;
+    let value = table.get_mut(*first)?;
+    if rest.is_empty() {
+        return Some(value);
+    }
+    value_mut(value.as_table_mut()?, rest)
+}
+
+/// Creates the [state_dir] if it does not exist, along with a `.gitignore` that keeps its contents
+/// out of version control.
+pub fn create_state_dir(project_root: &Path) -> std::io::Result<PathBuf> {
+    let dir = state_dir(project_root);
+    std::fs::create_dir_all(&dir)?;
+    write_if_changed(&dir.join(".gitignore"), "*\n")?;
+    Ok(dir)
+}
+
+/// Returns the profile configured with the `[tool.tire].profile` setting of the given
+/// `pyproject.toml`, if any.
+pub fn configured_profile(pyproject_toml: &Table) -> Option<&str> {
+    pyproject_toml
+        .get("tool")?
+        .get("tire")?
+        .get("profile")?
+        .as_str()
+}
 
 /// Checks if the given string is contained in one of the [KNOWN_TOOLS].
 pub fn is_known_tool<S: Into<String>>(tool: S) -> bool {
@@ -47,6 +180,41 @@ pub enum Error {
     InvalidProfile(String),
 }
 
+/// The result of [Profile::materialize].
+#[derive(Debug)]
+pub struct Materialized {
+    /// The project root directory, i.e. the directory that contains the `pyproject.toml`.
+    pub project_root: PathBuf,
+
+    /// The path to the materialized `pyproject.toml` file in the [state_dir].
+    pub file: PathBuf,
+
+    /// The merged configuration that was written to [Self::file].
+    pub config: Table,
+}
+
+impl Materialized {
+    /// Returns the materialized configuration file path as a string, for passing it to tools.
+    pub fn file_arg(&self) -> String {
+        self.file.to_string_lossy().to_string()
+    }
+
+    /// Returns the directory in which Tire keeps its per-project state.
+    pub fn state_dir(&self) -> PathBuf {
+        self.file.parent().unwrap().to_path_buf()
+    }
+
+    /// Look up a Tire setting from the `[tool.tire]` section of the merged configuration. The
+    /// *path* is relative to the `[tool.tire]` table, e.g. `["test", "allow-no-tests"]`.
+    pub fn setting(&self, path: &[&str]) -> Option<&Value> {
+        let mut value = self.config.get("tool")?.get("tire")?;
+        for key in path {
+            value = value.get(key)?;
+        }
+        Some(value)
+    }
+}
+
 /// Represents a deserialized Tire profile.
 #[derive(Debug)]
 pub struct Profile {
@@ -61,13 +229,25 @@ pub struct Profile {
 impl Profile {
     /// Main entrypoint for loading a profile.
     ///
-    /// If [None] or [Some] with value `"default"` is specified, the [DEFAULT_PROFILE] is loaded.
-    /// Otherwise, a URL is expected and it is loaded from the network.
+    /// If [None] is specified, the profile configured in the project's `pyproject.toml` is loaded
+    /// (see [configured_profile]), or the [DEFAULT_PROFILE] if there is none. If [Some] with value
+    /// `"default"` is specified, the [DEFAULT_PROFILE] is loaded. Otherwise, a URL is expected and
+    /// it is loaded from the network.
     ///
     /// TODO: Support caching already fetched URLs on disk.
     pub fn load(name: Option<String>) -> Result<Self, Error> {
         match name {
-            None => Self::load_string("default".to_owned(), DEFAULT_PROFILE.to_owned()),
+            None => {
+                let pyproject_toml = match find_pyproject_toml(None) {
+                    Some(file) => std::fs::read_to_string(file)?.parse::<Table>()?,
+                    None => Table::new(),
+                };
+                Self::load(Some(
+                    configured_profile(&pyproject_toml)
+                        .unwrap_or("default")
+                        .to_owned(),
+                ))
+            }
             Some(name) => {
                 if name == *"default" {
                     Self::load_string("default".to_owned(), DEFAULT_PROFILE.to_owned())
@@ -197,41 +377,54 @@ impl Profile {
         merge_tables(&self.root, pyproject_toml)
     }
 
-    /// Writes the updated `pyproject.toml` to a `.tire/pyproject.toml` file in the project
-    /// root directory of the given working directory. If the project has no `pyproject.toml`,
-    /// the current working directory is assumed to be the project root.
+    /// Merges the profile with the `pyproject.toml` of the project of the given working directory,
+    /// without writing anything. If the project has no `pyproject.toml`, the current working
+    /// directory is assumed to be the project root.
     ///
-    /// Returns the path to the `.tire/pyproject.toml` file.
-    ///
-    /// TODO: Support Uv workspaces (see https://github.com/NiklasRosenstein/tire/issues/2)
-    pub fn materialize(&self, cwd: Option<PathBuf>) -> Result<PathBuf, Error> {
+    /// Returns the project root and the merged configuration, as [Self::materialize] would write
+    /// it.
+    pub fn merged_config(&self, cwd: Option<PathBuf>) -> Result<(PathBuf, Table), Error> {
         let cwd = cwd.ok_or("").or_else(|_| std::env::current_dir())?;
 
-        let pyproject_toml_file = find_pyproject_toml(Some(cwd));
-
-        // The project root is where we place the `.tire/pyproject.toml` file.
-        let project_root = if let Some(file) = pyproject_toml_file.clone() {
-            file.parent().unwrap().to_path_buf()
-        } else {
-            std::env::current_dir()?
-        };
-        let out_file = project_root.join(".tire").join("pyproject.toml");
-        std::fs::create_dir_all(out_file.parent().unwrap())?;
+        let pyproject_toml_file = find_pyproject_toml(Some(cwd.clone()));
+        let project_root = find_project_root(Some(cwd))?;
 
         // Load the project's configuration.
         let pyproject_toml = if let Some(file) = pyproject_toml_file {
-            let content = std::fs::read_to_string(file).unwrap();
+            let content = std::fs::read_to_string(file)?;
             content.parse::<toml::Table>()?
         } else {
             Table::new()
         };
 
-        // Merge the configuration and write it to the output file.
-        std::fs::write(
-            out_file.clone(),
-            toml::to_string(&self.merge(&pyproject_toml))?,
-        )?;
+        // Relative paths would be resolved against the state directory if it is outside the
+        // project.
+        let mut config = self.merge(&pyproject_toml);
+        if !state_dir(&project_root).starts_with(&project_root) {
+            rebase_paths(&mut config, &project_root);
+        }
+        Ok((project_root, config))
+    }
 
-        Ok(out_file)
+    /// Writes the updated `pyproject.toml` to a `pyproject.toml` file in the [state_dir] of the
+    /// project of the given working directory (`.tire/` by default), see [Self::merged_config].
+    ///
+    /// Returns the path to the materialized file alongside the merged configuration.
+    ///
+    /// TODO: Support Uv workspaces (see https://github.com/NiklasRosenstein/tire/issues/2)
+    pub fn materialize(&self, cwd: Option<PathBuf>) -> Result<Materialized, Error> {
+        let (project_root, config) = self.merged_config(cwd)?;
+
+        // The state directory of the project root is where we place the materialized file.
+        // Concurrent Tire processes may read the file while we write it, so we only write it if it
+        // changed, and atomically.
+        let out_file = create_state_dir(&project_root)?.join("pyproject.toml");
+        write_if_changed(&out_file, toml::to_string(&config)?)?;
+
+        Ok(Materialized {
+            project_root,
+            file: out_file,
+            config,
+        })
     }
 }
@@ -1,34 +1,335 @@
 //! Implements the behaviour of the `tire run` command.
 
-use crate::utils::{run_command_or_exit, string_vec};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
-pub fn run(args: Vec<String>) {
-    // Extract options until the first positional argument to pass to `uv run`.
-    // TODO: Should we assume options with the `--` prefix to always consume an additional arg?
-    let mut uv_args = Vec::new();
-    let mut target_args = Vec::new();
-    let mut target: Option<String> = None;
-    for arg in args {
-        if target.is_none() {
-            if !arg.starts_with("-") {
-                target = Some(arg);
-            } else {
-                uv_args.push(arg);
+use toml::Value;
+
+use crate::{
+    profile::Profile,
+    utils::{Outcome, run_command_or_exit, string_vec},
+};
+
+/// How `tire run module:func` turns the command-line arguments into a call of the function.
+/// Configured with `[tool.tire.run].adapter`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Adapter {
+    /// Wrap the function in a `cyclopts.App`.
+    #[default]
+    Cyclopts,
+
+    /// Run the function with `typer.run`.
+    Typer,
+
+    /// Run the function with `fire.Fire`.
+    Fire,
+
+    /// Derive an `argparse` parser from the function's signature. Needs no extra dependency.
+    ArgparseFromSignature,
+
+    /// Call the function without arguments. The command-line arguments are left in `sys.argv`.
+    None,
+}
+
+impl FromStr for Adapter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cyclopts" => Ok(Adapter::Cyclopts),
+            "typer" => Ok(Adapter::Typer),
+            "fire" => Ok(Adapter::Fire),
+            "argparse-from-signature" => Ok(Adapter::ArgparseFromSignature),
+            "none" => Ok(Adapter::None),
+            _ => Err(format!("invalid adapter {s:?}")),
+        }
+    }
+}
+
+impl Adapter {
+    /// Returns the package that the adapter needs, if any.
+    pub fn requirement(self) -> Option<&'static str> {
+        match self {
+            Adapter::Cyclopts => Some("cyclopts>=3.0.0,<4.0.0"),
+            Adapter::Typer => Some("typer"),
+            Adapter::Fire => Some("fire"),
+            Adapter::ArgparseFromSignature | Adapter::None => None,
+        }
+    }
+
+    /// Returns the Python code that calls `target` with the arguments in `sys.argv`, following
+    /// [BOOTSTRAP].
+    fn code(self) -> &'static str {
+        match self {
+            Adapter::Cyclopts => CYCLOPTS,
+            Adapter::Typer => TYPER,
+            Adapter::Fire => FIRE,
+            Adapter::ArgparseFromSignature => ARGPARSE_FROM_SIGNATURE,
+            Adapter::None => NONE,
+        }
+    }
+}
+
+const CYCLOPTS: &str = r#"
+import cyclopts
+app = cyclopts.App(name=sys.argv[0], version_flags=[])
+app.default(target)
+app()
+"#;
+
+const TYPER: &str = r#"
+import typer
+typer.run(target)
+"#;
+
+const FIRE: &str = r#"
+import fire
+fire.Fire(target, name=sys.argv[0])
+"#;
+
+const ARGPARSE_FROM_SIGNATURE: &str = r#"
+import argparse, typing
+try:
+    hints = typing.get_type_hints(target)
+except Exception:
+    hints = {}
+parser = argparse.ArgumentParser(prog=sys.argv[0], description=inspect.getdoc(target))
+positional, keyword = [], []
+for param in inspect.signature(target).parameters.values():
+    annotation = hints.get(param.name, param.annotation)
+    convert = annotation if annotation in (int, float, str) else None
+    has_default = param.default is not param.empty
+    if param.kind == param.VAR_POSITIONAL:
+        parser.add_argument(param.name, nargs="*", type=convert)
+        positional.append((param.name, True))
+    elif param.kind == param.VAR_KEYWORD:
+        continue
+    elif param.kind == param.POSITIONAL_ONLY or (param.kind == param.POSITIONAL_OR_KEYWORD and not has_default):
+        if has_default:
+            parser.add_argument(param.name, nargs="?", type=convert, default=param.default)
+        else:
+            parser.add_argument(param.name, type=convert)
+        positional.append((param.name, False))
+    else:
+        flag = "--" + param.name.replace("_", "-")
+        if annotation is bool or isinstance(param.default, bool):
+            parser.add_argument(flag, dest=param.name, action=argparse.BooleanOptionalAction, default=has_default and param.default)
+        else:
+            parser.add_argument(flag, dest=param.name, type=convert, default=param.default if has_default else None, required=not has_default)
+        keyword.append(param.name)
+namespace = vars(parser.parse_args(sys.argv[1:]))
+args = []
+for name, variadic in positional:
+    if variadic:
+        args.extend(namespace[name])
+    else:
+        args.append(namespace[name])
+result = target(*args, **{name: namespace[name] for name in keyword})
+if result is not None:
+    print(result)
+"#;
+
+const NONE: &str = r#"
+target()
+"#;
+
+/// The start of the bootstrap script, which takes the target as its first argument, imports the
+/// module and resolves the attributes. Async functions are run with `asyncio.run`. The target is
+/// never interpolated into the script, so it can't inject code.
+const BOOTSTRAP: &str = r#"import asyncio, functools, importlib, inspect, sys
+sys.argv = sys.argv[1:]
+module, _, attributes = sys.argv[0].partition(":")
+target = importlib.import_module(module)
+for name in attributes.split("."):
+    target = getattr(target, name)
+if inspect.iscoroutinefunction(target):
+    coroutine_function = target
+    @functools.wraps(coroutine_function)
+    def target(*args, **kwargs):
+        return asyncio.run(coroutine_function(*args, **kwargs))
+"#;
+
+/// Error type for parsing a [Target].
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("invalid target {0:?}, expected `module:function` with dotted identifiers on both sides")]
+pub struct InvalidTarget(String);
+
+/// A function to call with `tire run module:func`, e.g. `pkg.mod:Class.method`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    /// The dotted name of the module to import.
+    pub module: String,
+
+    /// The dotted attributes to resolve in the module.
+    pub attributes: String,
+}
+
+/// Checks if *name* is a Python identifier.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|x| x == '_' || x.is_alphabetic())
+        && chars.all(|x| x == '_' || x.is_alphanumeric())
+}
+
+/// Checks if *name* is a dot-separated sequence of Python identifiers.
+fn is_dotted_identifier(name: &str) -> bool {
+    name.split('.').all(is_identifier)
+}
+
+impl FromStr for Target {
+    type Err = InvalidTarget;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((module, attributes))
+                if is_dotted_identifier(module) && is_dotted_identifier(attributes) =>
+            {
+                Ok(Target {
+                    module: module.to_owned(),
+                    attributes: attributes.to_owned(),
+                })
             }
-        } else {
-            target_args.push(arg);
+            _ => Err(InvalidTarget(s.to_owned())),
         }
     }
+}
 
-    if target.is_none() {
-        eprintln!("Missing positional argument");
-        std::process::exit(1);
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.module, self.attributes)
     }
-    let target = target.unwrap();
+}
 
-    eprintln!("uv_args={uv_args:?}");
-    eprintln!("target={target:?}");
-    eprintln!("target_args={target_args:?}");
+/// Returns the Python script that calls the target given as its first argument with the given
+/// adapter.
+pub fn bootstrap(adapter: Adapter) -> String {
+    format!("{BOOTSTRAP}{}", adapter.code().trim_start())
+}
+
+/// Returns the adapter configured with `[tool.tire.run].adapter` in the `pyproject.toml` of the
+/// project of the given working directory or in the *profile*. The configuration is merged in
+/// memory rather than materialized, as `tire run` may be invoked outside of a project.
+pub fn configured_adapter(profile: &Profile, cwd: Option<PathBuf>) -> Adapter {
+    let Ok((_, config)) = profile.merged_config(cwd) else {
+        return Adapter::default();
+    };
+    config
+        .get("tool")
+        .and_then(|x| x.get("tire"))
+        .and_then(|x| x.get("run"))
+        .and_then(|x| x.get("adapter"))
+        .and_then(Value::as_str)
+        .and_then(|x| {
+            x.parse::<Adapter>()
+                .map_err(|err| log::warn!("Ignoring `[tool.tire.run].adapter`: {err}"))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// The options of `uv run` that take a value, as of uv 0.8. Options that are not listed are
+/// assumed to be flags. The `test_uv_run_value_options` test checks this list against the
+/// installed uv. To regenerate it, run:
+///
+/// ```sh
+/// uv run --help | sed -nE 's/^ +(-[a-zA-Z], )?(--[a-z-]+) <.*/\1\2/p' | tr -s ', ' '\n' | sort
+/// ```
+pub const UV_RUN_VALUE_OPTIONS: &[&str] = &[
+    "--allow-insecure-host",
+    "--cache-dir",
+    "--color",
+    "--config-file",
+    "--config-setting",
+    "--default-index",
+    "--directory",
+    "--env-file",
+    "--exclude-newer",
+    "--exclude-newer-package",
+    "--extra",
+    "--extra-index-url",
+    "--find-links",
+    "--fork-strategy",
+    "--group",
+    "--index",
+    "--index-strategy",
+    "--index-url",
+    "--keyring-provider",
+    "--link-mode",
+    "--no-binary-package",
+    "--no-build-isolation-package",
+    "--no-build-package",
+    "--no-extra",
+    "--no-group",
+    "--only-group",
+    "--package",
+    "--prerelease",
+    "--project",
+    "--python",
+    "--refresh-package",
+    "--reinstall-package",
+    "--resolution",
+    "--upgrade-package",
+    "--with",
+    "--with-editable",
+    "--with-requirements",
+    "-C",
+    "-P",
+    "-f",
+    "-i",
+    "-p",
+];
+
+/// The arguments of `tire run`, split into the options for `uv run`, the target and the arguments
+/// for the target.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunArgs {
+    pub uv_args: Vec<String>,
+    pub target: String,
+    pub target_args: Vec<String>,
+}
+
+/// Split the arguments of `tire run` at the target, which is the first argument that is neither an
+/// option nor the value of an option in [UV_RUN_VALUE_OPTIONS]. A `--` ends the options, and the
+/// argument after it is the target even if it starts with a dash. Returns [None] if there is no
+/// target.
+pub fn split_args(args: Vec<String>) -> Option<RunArgs> {
+    let mut uv_args = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            let target = args.next()?;
+            return Some(RunArgs {
+                uv_args,
+                target,
+                target_args: args.collect(),
+            });
+        }
+        if !arg.starts_with('-') {
+            return Some(RunArgs {
+                uv_args,
+                target: arg,
+                target_args: args.collect(),
+            });
+        }
+        // Values can also be attached, as in `--python=3.12`.
+        let takes_value = UV_RUN_VALUE_OPTIONS.contains(&arg.as_str());
+        uv_args.push(arg);
+        if takes_value {
+            uv_args.push(args.next()?);
+        }
+    }
+    None
+}
+
+pub fn run(args: Vec<String>) {
+    let Some(RunArgs {
+        uv_args,
+        target,
+        target_args,
+    }) = split_args(args)
+    else {
+        eprintln!("[tire] Missing positional argument");
+        std::process::exit(1);
+    };
 
     let mut uv_command: Vec<String>;
 
@@ -39,37 +340,41 @@ pub fn run(args: Vec<String>) {
         uv_command.extend(uv_args);
         uv_command.push(String::from(target.strip_prefix("@").unwrap()));
     }
-    // If the target contains a colon, it is a function call in a module. We wrap it into a
-    // `cyclopts.App` for advanced argument parsing and help formatting.
+    // If the target contains a colon, it is a function call in a module. The configured adapter
+    // turns the command-line arguments into a call of the function.
     else if target.contains(":") {
-        let module = target.split(':').next().unwrap();
-        let func = target.split(':').nth(1).unwrap();
-        let code = format!(
-            "import sys, cyclopts, {module}; \
-            app = cyclopts.App(name='{module}:{func}', version_flags=[]); \
-            app.default({module}.{func}); \
-            app();"
-        );
-        uv_command = string_vec![
-            "uv",
-            "run",
-            "--with",
-            "cyclopts>=3.0.0,<4.0.0",
+        let target = match target.parse::<Target>() {
+            Ok(target) => target,
+            Err(err) => {
+                eprintln!("[tire] {err}");
+                Outcome::Error.exit();
+            }
+        };
+        let adapter = Profile::load(None)
+            .map(|profile| configured_adapter(&profile, None))
+            .unwrap_or_default();
+        uv_command = string_vec!["uv", "run"];
+        if let Some(requirement) = adapter.requirement() {
+            uv_command.extend(string_vec!["--with", requirement]);
+        }
+        uv_command.extend(uv_args);
+        uv_command.extend(string_vec![
             "python",
             "-c",
-            code.as_str()
-        ];
+            bootstrap(adapter),
+            target.to_string()
+        ]);
     }
     // Otherwise we pass it to UV directly.
     else {
         uv_command = string_vec!["uv", "run"];
+        uv_command.extend(uv_args);
+        uv_command.push(target);
     }
 
     // Append the arguments for the called target.
     uv_command.extend(target_args);
 
-    eprintln!("uv_command={uv_command:?}");
-
     // Invoke the command.
     run_command_or_exit(uv_command)
 }
@@ -0,0 +1,307 @@
+//! Helpers for selecting the files that a command should operate on.
+
+use std::path::{Path, PathBuf};
+
+use crate::utils::{Outcome, find_project_root};
+
+/// Error type for selecting files.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("`git {0}` failed: {1}")]
+    Git(String, String),
+}
+
+/// Selects files based on their Git status instead of passing them explicitly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitSelection {
+    /// Files with uncommitted changes (staged or not), and untracked files.
+    Changed,
+
+    /// Files changed since the merge base with the given Git ref, including uncommitted changes
+    /// and untracked files.
+    Since(String),
+
+    /// Files with staged changes, e.g. for a pre-commit hook.
+    Staged,
+}
+
+/// Run `git` with the given arguments in *cwd* and return its stdout.
+pub fn git(cwd: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::Git(
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Returns the existing files in the project that are selected by *selection*, relative to the
+/// project root.
+pub fn git_files(project_root: &Path, selection: &GitSelection) -> Result<Vec<PathBuf>, Error> {
+    // With `--relative`, paths are relative to (and limited to) the project root rather than the
+    // repository root.
+    let output = match selection {
+        GitSelection::Changed => git(
+            project_root,
+            &[
+                "diff",
+                "--name-only",
+                "--relative",
+                "--diff-filter=d",
+                "HEAD",
+            ],
+        )?,
+        GitSelection::Staged => git(
+            project_root,
+            &[
+                "diff",
+                "--cached",
+                "--name-only",
+                "--relative",
+                "--diff-filter=d",
+            ],
+        )?,
+        GitSelection::Since(git_ref) => {
+            let merge_base = git(project_root, &["merge-base", git_ref, "HEAD"])?;
+            git(
+                project_root,
+                &[
+                    "diff",
+                    "--name-only",
+                    "--relative",
+                    "--diff-filter=d",
+                    merge_base.trim(),
+                ],
+            )?
+        }
+    };
+    let output = match selection {
+        GitSelection::Staged => output,
+        _ => {
+            output
+                + &git(
+                    project_root,
+                    &["ls-files", "--others", "--exclude-standard"],
+                )?
+        }
+    };
+
+    let mut files: Vec<PathBuf> = output
+        .lines()
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Returns the given paths relative to the project root as paths relative to *cwd*, or as
+/// absolute paths if they are not inside it.
+pub fn relative_to_cwd(cwd: &Path, project_root: &Path, paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| {
+            let path = project_root.join(path);
+            path.strip_prefix(cwd)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Returns the Python files selected by *selection* in the project of *cwd*, relative to *cwd*,
+/// for passing them to a tool. Returns [None] if no Python files are selected, in which case the
+/// tool should not be run at all.
+pub fn python_files(cwd: &Path, selection: &GitSelection) -> Result<Option<Vec<String>>, Error> {
+    let project_root = find_project_root(Some(cwd.to_path_buf()))?;
+    let files: Vec<PathBuf> = git_files(&project_root, selection)?
+        .into_iter()
+        .filter(|x| is_python_file(x))
+        .collect();
+    if files.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(relative_to_cwd(cwd, &project_root, &files)))
+}
+
+/// Returns the test files affected by the Python files selected by *selection* in the project of
+/// *cwd*, relative to *cwd*, for passing them to pytest. Returns [None] if no tests are affected.
+pub fn test_files(cwd: &Path, selection: &GitSelection) -> Result<Option<Vec<String>>, Error> {
+    let project_root = find_project_root(Some(cwd.to_path_buf()))?;
+    let files = affected_tests(&project_root, &git_files(&project_root, selection)?);
+    if files.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(relative_to_cwd(cwd, &project_root, &files)))
+}
+
+/// A function that selects files from Git, such as [python_files] or [test_files].
+pub type SelectFiles = fn(&Path, &GitSelection) -> Result<Option<Vec<String>>, Error>;
+
+/// Returns the *files* to operate on, replacing them with the files that *select* selects from Git
+/// if a *selection* is given. Returns [Err] with the outcome to exit with if no files are selected
+/// (after reporting it) or the selection failed.
+pub fn resolve_files(
+    files: Vec<String>,
+    selection: Option<&GitSelection>,
+    select: SelectFiles,
+) -> Result<Vec<String>, Outcome> {
+    let Some(selection) = selection else {
+        return Ok(files);
+    };
+    match select(&std::env::current_dir().unwrap(), selection) {
+        Ok(Some(files)) => Ok(files),
+        Ok(None) => {
+            eprintln!("[tire] No changed files selected.");
+            Err(Outcome::Success)
+        }
+        Err(err) => {
+            eprintln!("[tire] Failed to select changed files: {err}");
+            Err(Outcome::Error)
+        }
+    }
+}
+
+/// Checks if the given path is a Python source file.
+pub fn is_python_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "py" || ext == "pyi")
+}
+
+/// Checks if the given path is a pytest test file, i.e. `test_*.py` or `*_test.py`.
+pub fn is_test_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|x| x.to_string_lossy()) else {
+        return false;
+    };
+    name.ends_with(".py") && (name.starts_with("test_") || name.ends_with("_test.py"))
+}
+
+/// Returns the names under which the Python file at *path* (relative to the project root) can be
+/// imported, e.g. `pkg.mod` for both `pkg/mod.py` and `src/pkg/mod.py`.
+pub fn module_names(path: &Path) -> Vec<String> {
+    let mut parts: Vec<String> = path
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.last().is_some_and(|x| x == "__init__") {
+        parts.pop();
+    }
+    if parts.is_empty() {
+        return vec![];
+    }
+
+    let mut names = vec![parts.join(".")];
+    if parts.len() > 1 && parts[0] == "src" {
+        names.push(parts[1..].join("."));
+    }
+    names
+}
+
+/// Returns the modules imported by the given Python source code with absolute `import` and
+/// `from ... import` statements. For `from a import b`, both `a` and `a.b` are returned, as `b`
+/// may be a submodule.
+pub fn imported_modules(source: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("import ") {
+            for name in rest.split(',') {
+                if let Some(name) = name.split_whitespace().next() {
+                    modules.push(name.to_owned());
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("from ") {
+            let Some((module, names)) = rest.split_once(" import ") else {
+                continue;
+            };
+            let module = module.trim();
+            if module.starts_with('.') {
+                continue;
+            }
+
+            // Collect the names of a parenthesized import that spans multiple lines.
+            let mut names = names.to_owned();
+            if names.contains('(') && !names.contains(')') {
+                for line in lines.by_ref() {
+                    names.push_str(line);
+                    if line.contains(')') {
+                        break;
+                    }
+                }
+            }
+
+            modules.push(module.to_owned());
+            for name in names.trim_matches(|c| "() \\".contains(c)).split(',') {
+                let name = name.split('#').next().unwrap_or_default();
+                if let Some(name) = name
+                    .trim_matches(|c| "() ".contains(c))
+                    .split_whitespace()
+                    .next()
+                {
+                    modules.push(format!("{module}.{name}"));
+                }
+            }
+        }
+    }
+    modules
+}
+
+/// Returns the test files in the project that are affected by changes to the given files (as
+/// paths relative to the project root): changed test files themselves, and test files that
+/// import one of the changed modules or one of their submodules.
+pub fn affected_tests(project_root: &Path, changed: &[PathBuf]) -> Vec<PathBuf> {
+    let changed: Vec<&PathBuf> = changed.iter().filter(|x| is_python_file(x)).collect();
+    let modules: Vec<String> = changed
+        .iter()
+        .filter(|x| !is_test_file(x))
+        .flat_map(|x| module_names(x))
+        .collect();
+
+    let mut affected: Vec<PathBuf> = changed
+        .iter()
+        .filter(|x| is_test_file(x) && project_root.join(x).exists())
+        .map(|x| x.to_path_buf())
+        .collect();
+    if modules.is_empty() {
+        return affected;
+    }
+
+    for entry in ignore::Walk::new(project_root).filter_map(Result::ok) {
+        let path = entry.path();
+        if !is_test_file(path) {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(project_root) else {
+            continue;
+        };
+        if affected.iter().any(|x| x == relative) {
+            continue;
+        }
+        let Ok(source) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let imports_changed_module = imported_modules(&source).iter().any(|imported| {
+            modules
+                .iter()
+                .any(|module| imported == module || imported.starts_with(&format!("{module}.")))
+        });
+        if imports_changed_module {
+            affected.push(relative.to_path_buf());
+        }
+    }
+
+    affected.sort();
+    affected
+}
@@ -0,0 +1,168 @@
+//! Implements splitting the test suite into shards for `tire test --shard`.
+//!
+//! Tests are partitioned by file, such that each shard receives a similar share of the total
+//! test duration. Durations of previous runs are kept in a timing file; tests without a known
+//! duration are assumed to take as long as the average test.
+
+use std::{collections::BTreeMap, fmt::Display, path::Path, str::FromStr};
+
+use crate::junit::TestReport;
+
+/// Error type for parsing a [Shard].
+#[derive(Debug, thiserror::Error)]
+#[error("invalid shard {0:?}, expected `<index>/<count>` with 1 <= index <= count")]
+pub struct InvalidShard(String);
+
+/// Selects one of *count* shards of the test suite, where *index* starts at 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl FromStr for Shard {
+    type Err = InvalidShard;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidShard(s.to_owned());
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let count: usize = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(invalid());
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// How to weigh test files when partitioning them into shards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Balance the number of tests per shard.
+    File,
+
+    /// Balance the historical duration of tests per shard.
+    Duration,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(Strategy::File),
+            "duration" => Ok(Strategy::Duration),
+            _ => Err(format!("invalid shard strategy {s:?}")),
+        }
+    }
+}
+
+/// Maps test IDs (see [junit_id]) to their duration in seconds.
+pub type Durations = BTreeMap<String, f64>;
+
+/// Load the durations from the given timing file. Returns an empty map if the file does not exist
+/// or cannot be parsed.
+pub fn load_durations(path: &Path) -> Durations {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|x| serde_json::from_str(&x).ok())
+        .unwrap_or_default()
+}
+
+/// Update the timing file with the durations of the tests in the given report.
+pub fn update_durations(path: &Path, report: &TestReport) -> std::io::Result<()> {
+    let mut durations = load_durations(path);
+    for test in &report.tests {
+        durations.insert(test.id(), test.duration);
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&durations)?)
+}
+
+/// Converts a pytest node ID (e.g. `tests/test_foo.py::TestFoo::test_bar[a::b]`) to the test ID
+/// used in JUnit reports (e.g. `tests.test_foo.TestFoo::test_bar[a::b]`), the same way pytest
+/// does.
+pub fn junit_id(node_id: &str) -> String {
+    let (path, params) = match node_id.find('[') {
+        Some(index) => node_id.split_at(index),
+        None => (node_id, ""),
+    };
+    let mut names: Vec<String> = path.split("::").map(String::from).collect();
+    names[0] = names[0].replace('/', ".");
+    if let Some(stripped) = names[0].strip_suffix(".py") {
+        names[0] = stripped.to_owned();
+    }
+    let name = names.pop().unwrap_or_default();
+    if names.is_empty() {
+        return format!("{name}{params}");
+    }
+    format!("{}::{name}{params}", names.join("."))
+}
+
+/// Returns the files of the given test node IDs that belong to the shard, in the order they
+/// appear in.
+pub fn select(
+    node_ids: &[String],
+    shard: Shard,
+    strategy: Strategy,
+    durations: &Durations,
+) -> Vec<String> {
+    let known: Vec<f64> = node_ids
+        .iter()
+        .filter_map(|x| durations.get(&junit_id(x)).copied())
+        .collect();
+    let default_duration = if known.is_empty() {
+        1.0
+    } else {
+        known.iter().sum::<f64>() / known.len() as f64
+    };
+
+    // Sum up the weight of the tests per file.
+    let mut files: Vec<(String, f64)> = Vec::new();
+    for node_id in node_ids {
+        let file = node_id.split("::").next().unwrap().to_owned();
+        let weight = match strategy {
+            Strategy::File => 1.0,
+            Strategy::Duration => durations
+                .get(&junit_id(node_id))
+                .copied()
+                .unwrap_or(default_duration),
+        };
+        match files.iter_mut().find(|(f, _)| *f == file) {
+            Some((_, total)) => *total += weight,
+            None => files.push((file, weight)),
+        }
+    }
+
+    let shards = partition(&files, shard.count);
+    let selected = &shards[shard.index - 1];
+    files
+        .into_iter()
+        .map(|(file, _)| file)
+        .filter(|file| selected.contains(file))
+        .collect()
+}
+
+/// Partitions the weighted items into *count* groups of similar total weight, by assigning the
+/// heaviest remaining item to the lightest group. The result only depends on the input, so every
+/// CI runner computes the same partitions.
+pub fn partition(items: &[(String, f64)], count: usize) -> Vec<Vec<String>> {
+    let mut items: Vec<&(String, f64)> = items.iter().collect();
+    items.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut groups: Vec<(f64, Vec<String>)> = vec![(0.0, Vec::new()); count];
+    for (item, weight) in items {
+        let lightest = groups
+            .iter_mut()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap();
+        lightest.0 += weight;
+        lightest.1.push(item.clone());
+    }
+    groups.into_iter().map(|(_, items)| items).collect()
+}
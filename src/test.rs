@@ -1,57 +1,540 @@
 //! Implements the `tire test` command.
 
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use toml::Value;
+
 use crate::{
-    profile::Profile,
-    utils::{run_command_or_exit, string_vec},
+    junit::TestReport,
+    profile::{Materialized, Profile},
+    select::{GitSelection, affected_tests, relative_to_cwd, resolve_files, test_files},
+    shard::{self, Shard, Strategy, load_durations, update_durations},
+    utils::{
+        ExitCodes, Outcome, capture_command, find_project_root, fnmatch, run_tool, string_vec,
+    },
 };
 
-pub fn test(
-    files: Vec<String>,
-    _allow_no_tests: bool,
-    parallel: Option<i32>,
-    filter: Option<String>,
-    doctests: bool,
-) {
-    // Write the merged configuration to a temporary file
-    let pyproject_toml = Profile::load(None).unwrap().materialize(None).unwrap();
+/// The default directory, relative to the project root, into which coverage reports are written.
+const DEFAULT_COVERAGE_DIR: &str = "coverage";
 
-    // Run dmypy with the merged config file
-    let mut uv_command = string_vec![
-        "uv",
-        "run",
-        "--with",
-        "pytest",
-        "--with",
-        "pytest-xdist",
+/// Documentation files whose doctests are run by default, if any files match.
+const DEFAULT_DOCTEST_GLOBS: [&str; 3] = ["README.md", "docs/**/*.md", "*.rst"];
+
+/// The default number of slowest tests to show in the summary.
+const DEFAULT_SLOWEST: usize = 5;
+
+/// Machine-readable formats for the report written by `tire test --report`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// A JSON document with a summary and the outcome and duration of every test.
+    Json,
+}
+
+/// Options for the `tire test` command.
+#[derive(Clone, Debug, Default)]
+pub struct TestOptions {
+    /// Files or directories to test recursively. Defaults to the current working directory.
+    pub files: Vec<String>,
+
+    /// Do not error when no tests are discovered.
+    pub allow_no_tests: bool,
+
+    /// Number of parallel tests to run. Determined automatically if [None].
+    pub parallel: Option<i32>,
+
+    /// Run only tests that contain the given substring.
+    pub filter: Option<String>,
+
+    /// Files or directories to ignore during test collection.
+    pub ignore: Vec<String>,
+
+    /// Run Python doctests.
+    pub doctests: bool,
+
+    /// Run doctests in documentation files matching the `[tool.tire.test].doctest-globs`.
+    pub doctest_globs: bool,
+
+    /// Measure code coverage with `pytest-cov`.
+    pub coverage: bool,
+
+    /// The directory to write coverage reports to. Overrides `[tool.tire.test].coverage-dir`.
+    pub coverage_dir: Option<PathBuf>,
+
+    /// Write a JUnit XML report to this path. If [None], the report is written to Tire's state
+    /// directory, as Tire needs it to print its summary.
+    pub junit_xml: Option<PathBuf>,
+
+    /// Write a machine-readable report in this format.
+    pub report: Option<ReportFormat>,
+
+    /// The file to write the machine-readable report to. Defaults to `test-report.json` in
+    /// Tire's state directory.
+    pub report_file: Option<PathBuf>,
+
+    /// Run only the tests in this shard of the test suite.
+    pub shard: Option<Shard>,
+
+    /// Run only the tests that failed in the last run.
+    pub failed: bool,
+
+    /// Rerun failing tests up to this many times. Overrides `[tool.tire.test].retries`.
+    pub retries: Option<u32>,
+}
+
+/// Returns the directory that coverage reports are written to.
+fn coverage_dir(options: &TestOptions, materialized: &Materialized) -> PathBuf {
+    let dir = options.coverage_dir.clone().unwrap_or_else(|| {
+        materialized
+            .setting(&["test", "coverage-dir"])
+            .and_then(Value::as_str)
+            .unwrap_or(DEFAULT_COVERAGE_DIR)
+            .into()
+    });
+    materialized.project_root.join(dir)
+}
+
+/// Build the `pytest` command with the options that apply to both collecting and running tests.
+fn pytest_command(options: &TestOptions, materialized: &Materialized) -> Vec<String> {
+    let mut uv_command = string_vec!["uv", "run", "--with", "pytest", "--with", "pytest-xdist"];
+    if options.coverage {
+        uv_command.extend(string_vec!["--with", "pytest-cov"]);
+    }
+
+    // Pytest would otherwise use the directory of the config file as the rootdir, which is the
+    // `.tire/` directory. Node IDs are relative to the rootdir.
+    uv_command.extend(string_vec![
         "pytest",
         "--config-file",
-        pyproject_toml.to_string_lossy().to_string()
-    ];
-
-    uv_command.push("-n".to_owned());
-    uv_command.push(
-        parallel
-            .map(|x| x.to_string())
-            .unwrap_or("auto".to_string()),
-    );
+        materialized.file_arg(),
+        "--rootdir",
+        materialized.project_root.to_string_lossy()
+    ]);
 
-    if let Some(filter) = filter {
+    if let Some(filter) = &options.filter {
         uv_command.push("-k".to_owned());
-        uv_command.push(filter);
+        uv_command.push(filter.clone());
+    }
+
+    for path in &options.ignore {
+        uv_command.push("--ignore".to_owned());
+        uv_command.push(path.clone());
     }
 
-    if doctests {
+    // Pytest records the failed tests in its cache. Without `--last-failed-no-failures none`, it
+    // would run all tests if none failed.
+    if options.failed {
+        uv_command.extend(string_vec![
+            "--last-failed",
+            "--last-failed-no-failures",
+            "none"
+        ]);
+    }
+
+    if options.doctests {
         uv_command.push("--doctest-modules".to_owned());
-        // TODO(@niklas): Add support for --doctest-glob (with as little config required by the user as possible)
+        if options.doctest_globs {
+            for glob in doctest_globs(materialized) {
+                uv_command.push("--doctest-glob".to_owned());
+                uv_command.push(glob);
+            }
+        }
     }
 
-    if files.is_empty() {
-        uv_command.push(".".to_owned());
+    uv_command
+}
+
+/// Returns the files or directories to pass to pytest.
+fn files_arg(options: &TestOptions) -> Vec<String> {
+    if options.files.is_empty() {
+        vec![".".to_owned()]
     } else {
-        uv_command.extend(files);
+        options.files.clone()
+    }
+}
+
+/// Collect the tests and return the files of the tests that belong to the given shard. Returns
+/// [Err] with the outcome of the collection if it failed.
+fn shard_files(
+    shard: Shard,
+    options: &TestOptions,
+    materialized: &Materialized,
+) -> Result<Vec<String>, Outcome> {
+    let mut uv_command = pytest_command(options, materialized);
+    uv_command.extend(string_vec!["--collect-only", "-q"]);
+    uv_command.extend(files_arg(options));
+    let (status, stdout) = capture_command(uv_command);
+    let outcome = ExitCodes::pytest()
+        .with(5, Outcome::Success)
+        .outcome(status.code());
+    if outcome != Outcome::Success {
+        print!("{stdout}");
+        return Err(outcome);
+    }
+
+    // With `-q`, pytest prints one node ID per line, followed by a summary.
+    let node_ids: Vec<String> = stdout
+        .lines()
+        .filter(|x| x.contains("::") && !x.starts_with(' '))
+        .map(String::from)
+        .collect();
+
+    let strategy = materialized
+        .setting(&["test", "shard-strategy"])
+        .and_then(Value::as_str)
+        .and_then(|x| {
+            x.parse::<Strategy>()
+                .map_err(|err| log::warn!("Ignoring `[tool.tire.test].shard-strategy`: {err}"))
+                .ok()
+        })
+        .unwrap_or(Strategy::Duration);
+    let durations = load_durations(&durations_file(materialized));
+    let files = shard::select(&node_ids, shard, strategy, &durations);
+    eprintln!(
+        "[tire] Shard {shard} runs {} of {} test file(s)",
+        files.len(),
+        node_ids
+            .iter()
+            .map(|x| x.split("::").next().unwrap())
+            .collect::<HashSet<_>>()
+            .len()
+    );
+
+    let files: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    let cwd = std::env::current_dir().unwrap();
+    Ok(relative_to_cwd(&cwd, &materialized.project_root, &files))
+}
+
+/// Returns the timing file that records the duration of tests for sharding.
+fn durations_file(materialized: &Materialized) -> PathBuf {
+    match materialized
+        .setting(&["test", "durations-file"])
+        .and_then(Value::as_str)
+    {
+        Some(file) => materialized.project_root.join(file),
+        None => materialized.state_dir().join("test-durations.json"),
+    }
+}
+
+pub fn test(options: TestOptions) -> Outcome {
+    // Write the merged configuration to a temporary file
+    let materialized = Profile::load(None).unwrap().materialize(None).unwrap();
+
+    // Select only the test files of this shard.
+    let mut options = options;
+    if let Some(shard) = options.shard {
+        match shard_files(shard, &options, &materialized) {
+            Ok(files) if files.is_empty() => {
+                eprintln!("[tire] Shard {shard} has no tests to run.");
+                return Outcome::Success;
+            }
+            Ok(files) => options.files = files,
+            Err(outcome) => return outcome,
+        }
+    }
+
+    let mut uv_command = pytest_command(&options, &materialized);
+    uv_command.extend(parallel_args(&options));
+
+    // Coverage is configured through the `[tool.coverage]` section of the materialized config
+    // (source directories, branch coverage, fail-under threshold). `pytest-cov` takes care of
+    // combining the coverage data of the xdist workers.
+    if options.coverage {
+        let dir = coverage_dir(&options, &materialized);
+        let dir = dir.to_string_lossy();
+        uv_command.extend(string_vec![
+            "--cov",
+            "--cov-config",
+            materialized.file_arg(),
+            "--cov-report",
+            "term-missing",
+            "--cov-report",
+            format!("html:{dir}/html"),
+            "--cov-report",
+            format!("xml:{dir}/coverage.xml"),
+            "--cov-report",
+            format!("lcov:{dir}/coverage.lcov")
+        ]);
+    }
+
+    // We always let pytest write a JUnit report, from which we derive our own summary.
+    let junit_xml = options
+        .junit_xml
+        .clone()
+        .unwrap_or_else(|| materialized.state_dir().join("junit.xml"));
+    let _ = std::fs::remove_file(&junit_xml);
+    uv_command.push("--junit-xml".to_owned());
+    uv_command.push(junit_xml.to_string_lossy().to_string());
+
+    uv_command.extend(files_arg(&options));
+
+    // Pytest exits with code 5 if no tests were collected, which is expected with `--failed` if
+    // no tests failed in the last run.
+    let allow_no_tests = options.allow_no_tests
+        || options.failed
+        || materialized
+            .setting(&["test", "allow-no-tests"])
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+    let mut exit_codes = ExitCodes::pytest();
+    if allow_no_tests {
+        exit_codes = exit_codes.with(5, Outcome::Success);
     }
 
     // Run the command
-    // TODO: Check if exit code == 5 (no tests found) and don't error if allow_no_tests is enabled
-    run_command_or_exit(uv_command);
+    let mut outcome = run_tool(uv_command, &exit_codes);
+
+    // Pytest does not write a report if it crashed or was interrupted early.
+    if junit_xml.exists() {
+        let mut report = TestReport::load(&junit_xml).unwrap();
+
+        let retries = options.retries.unwrap_or_else(|| {
+            materialized
+                .setting(&["test", "retries"])
+                .and_then(Value::as_integer)
+                .map(|x| x.max(0) as u32)
+                .unwrap_or(0)
+        });
+        if outcome == Outcome::Failure && retries > 0 {
+            outcome = retry_failed(&options, &materialized, &mut report, retries);
+
+            // The first run may also have failed because of insufficient coverage, which passing
+            // retries don't change.
+            if outcome == Outcome::Success && options.coverage {
+                outcome = check_coverage(&materialized);
+            }
+        }
+        let flaky = report.flaky();
+
+        print_summary(&report, &flaky, &materialized);
+        if let Err(err) = update_durations(&durations_file(&materialized), &report) {
+            log::warn!("Failed to update test durations: {err}");
+        }
+        if let Some(format) = options.report {
+            let report_file = options.report_file.clone().unwrap_or_else(|| {
+                materialized.state_dir().join(match format {
+                    ReportFormat::Json => "test-report.json",
+                })
+            });
+            write_report(&report, &flaky, format, &report_file);
+            eprintln!("[tire] Wrote test report to {}", report_file.display());
+        }
+    }
+
+    outcome
+}
+
+/// Returns the arguments for running tests in parallel with `pytest-xdist`.
+fn parallel_args(options: &TestOptions) -> Vec<String> {
+    let parallel = options
+        .parallel
+        .map(|x| x.to_string())
+        .unwrap_or("auto".to_string());
+    string_vec!["-n", parallel]
+}
+
+/// Rerun the failed tests of the *report* up to *retries* times, until they pass. Tests that pass
+/// on a retry are marked as flaky in the *report*. Returns the outcome of the retries.
+fn retry_failed(
+    options: &TestOptions,
+    materialized: &Materialized,
+    report: &mut TestReport,
+    retries: u32,
+) -> Outcome {
+    // The failure might not be caused by a test (e.g. a collection error or insufficient
+    // coverage), in which case retrying is pointless.
+    if report.failing().is_empty() {
+        return Outcome::Failure;
+    }
+
+    // Pytest's cache knows which tests failed, so we run them with `--last-failed`. Coverage
+    // would be incomplete, so we don't measure it again.
+    let options = TestOptions {
+        failed: true,
+        coverage: false,
+        ..options.clone()
+    };
+    let junit_xml = materialized.state_dir().join("junit-retry.xml");
+    for attempt in 1..=retries {
+        eprintln!(
+            "[tire] Retrying {} failed test(s) (attempt {attempt} of {retries})",
+            report.failing().len()
+        );
+        let _ = std::fs::remove_file(&junit_xml);
+        let mut uv_command = pytest_command(&options, materialized);
+        uv_command.extend(parallel_args(&options));
+        uv_command.push("--junit-xml".to_owned());
+        uv_command.push(junit_xml.to_string_lossy().to_string());
+        uv_command.extend(files_arg(&options));
+
+        let outcome = run_tool(uv_command, &ExitCodes::pytest());
+        if outcome == Outcome::Error || !junit_xml.exists() {
+            return Outcome::Error;
+        }
+
+        report.merge_retry(&TestReport::load(&junit_xml).unwrap());
+        if report.failing().is_empty() {
+            return Outcome::Success;
+        }
+    }
+
+    Outcome::Failure
+}
+
+/// Check the coverage measured by the last run with `--coverage` against the `fail_under`
+/// threshold of the `[tool.coverage.report]` section.
+fn check_coverage(materialized: &Materialized) -> Outcome {
+    let uv_command = string_vec![
+        "uv",
+        "run",
+        "--with",
+        "coverage",
+        "coverage",
+        "report",
+        "--rcfile",
+        materialized.file_arg()
+    ];
+    let (status, _) = capture_command(uv_command);
+    let outcome = ExitCodes::coverage().outcome(status.code());
+    if outcome == Outcome::Failure {
+        eprintln!("[tire] The tests passed on a retry, but the coverage is below `fail_under`.");
+    }
+    outcome
+}
+
+/// Run the tests whenever Python files in the project change. After a change, the test files
+/// affected by the change run first, and only if they pass, the rest of the tests. With a Git
+/// *selection*, the tests to run are selected again before every run.
+pub fn watch(options: TestOptions, selection: Option<GitSelection>) -> ! {
+    let project_root = find_project_root(None).unwrap();
+    crate::watch::watch(|changed| {
+        let options = match resolve_files(options.files.clone(), selection.as_ref(), test_files) {
+            Ok(files) => TestOptions {
+                files,
+                ..options.clone()
+            },
+            Err(outcome) => return outcome,
+        };
+        let affected = affected_tests(&project_root, changed);
+        if affected.is_empty() {
+            return test(options.clone());
+        }
+
+        let affected: Vec<String> = affected
+            .iter()
+            .map(|x| project_root.join(x).to_string_lossy().to_string())
+            .collect();
+        eprintln!(
+            "[tire] Running {} affected test file(s) first",
+            affected.len()
+        );
+        let outcome = test(TestOptions {
+            files: affected.clone(),
+            allow_no_tests: true,
+            ..options.clone()
+        });
+        if outcome != Outcome::Success {
+            return outcome;
+        }
+
+        // If every test file was affected, no tests remain and pytest collects nothing.
+        eprintln!("[tire] Running the remaining tests");
+        test(TestOptions {
+            ignore: affected,
+            allow_no_tests: true,
+            ..options.clone()
+        })
+    })
+}
+
+/// Returns the `[tool.tire.test].doctest-globs` (or [DEFAULT_DOCTEST_GLOBS]) that match at least
+/// one file in the project, converted to the syntax of pytest's `--doctest-glob`. Passing globs
+/// that match no files is harmless, but we want to keep the pytest command line short.
+fn doctest_globs(materialized: &Materialized) -> Vec<String> {
+    let globs: Vec<String> = match materialized.setting(&["test", "doctest-globs"]) {
+        Some(Value::Array(globs)) => globs
+            .iter()
+            .filter_map(|x| x.as_str().map(pytest_glob))
+            .collect(),
+        _ => DEFAULT_DOCTEST_GLOBS
+            .iter()
+            .map(|x| pytest_glob(x))
+            .collect(),
+    };
+    if globs.is_empty() {
+        return globs;
+    }
+
+    let files: Vec<String> = ignore::Walk::new(&materialized.project_root)
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|x| x.is_file()))
+        .filter_map(|entry| {
+            let path = entry.path().strip_prefix(&materialized.project_root).ok()?;
+            Some(path.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    globs
+        .into_iter()
+        .filter(|glob| files.iter().any(|file| matches_doctest_glob(glob, file)))
+        .collect()
+}
+
+/// Converts a glob to the syntax of pytest's `--doctest-glob`, which is matched with `fnmatch`.
+/// As `*` already matches path separators, `**/` is redundant.
+pub fn pytest_glob(glob: &str) -> String {
+    glob.replace("**/", "")
+}
+
+/// Checks if the given path relative to the project root matches a `--doctest-glob` the way
+/// pytest does: patterns without a path separator match the file name only, other patterns must
+/// match the end of the path.
+pub fn matches_doctest_glob(glob: &str, path: &str) -> bool {
+    if glob.contains('/') {
+        fnmatch(glob, path) || fnmatch(&format!("*/{glob}"), path)
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        fnmatch(glob, name)
+    }
+}
+
+/// Print a summary of the test outcomes, the slowest and the flaky tests to stderr.
+fn print_summary(report: &TestReport, flaky: &[String], materialized: &Materialized) {
+    let slowest = materialized
+        .setting(&["test", "slowest"])
+        .and_then(Value::as_integer)
+        .map(|x| x.max(0) as usize)
+        .unwrap_or(DEFAULT_SLOWEST);
+
+    let tests = report.slowest(slowest);
+    if !tests.is_empty() {
+        eprintln!("[tire] Slowest tests:");
+        for test in tests {
+            eprintln!("[tire]   {:>8.2}s  {}", test.duration, test.id());
+        }
+    }
+    if !flaky.is_empty() {
+        eprintln!("[tire] Flaky tests (passed on retry):");
+        for test in flaky {
+            eprintln!("[tire]   {test}");
+        }
+    }
+    eprintln!("[tire] {}", report.summary());
+}
+
+/// Write the report in the given format to a file.
+fn write_report(report: &TestReport, flaky: &[String], format: ReportFormat, path: &Path) {
+    let content = match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "summary": report.summary(),
+            "tests": report.tests,
+            "flaky": flaky,
+        }))
+        .unwrap(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).unwrap();
+    }
+    std::fs::write(path, content).unwrap();
 }
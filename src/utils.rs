@@ -8,22 +8,195 @@ macro_rules! string_vec {
     });
 }
 
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitStatus;
+
+use sha2::{Digest, Sha256};
 
 pub(crate) use string_vec;
 
-/// Run the given command. If the command exits with a non-zero status code, print to stderr
-/// and exit the process.
-pub fn run_command_or_exit(command: Vec<String>) {
+/// Run the given command and return its exit status.
+pub fn run_command(command: Vec<String>) -> ExitStatus {
     eprintln!("[tire] $ {command:?}");
     let program = &command[0];
     let mut proc = std::process::Command::new(program)
         .args(command[1..].iter())
         .spawn()
         .unwrap_or_else(|_| panic!("Failed to run program `{program}`"));
-    let status = proc
-        .wait()
+    proc.wait()
+        .unwrap_or_else(|_| panic!("Failed to wait for program `{program}`"))
+}
+
+/// Run the given command and return its exit status and standard output. Standard error is
+/// passed through to the current process.
+pub fn capture_command(command: Vec<String>) -> (ExitStatus, String) {
+    eprintln!("[tire] $ {command:?}");
+    let program = &command[0];
+    let output = std::process::Command::new(program)
+        .args(command[1..].iter())
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .unwrap_or_else(|_| panic!("Failed to run program `{program}`"));
+    (
+        output.status,
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    )
+}
+
+/// Run the given command with *input* on its standard input and return its exit status and
+/// standard output. Standard error is passed through to the current process.
+pub fn pipe_command(command: Vec<String>, input: &[u8]) -> (ExitStatus, String) {
+    eprintln!("[tire] $ {command:?}");
+    let program = &command[0];
+    let mut proc = std::process::Command::new(program)
+        .args(command[1..].iter())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .spawn()
+        .unwrap_or_else(|_| panic!("Failed to run program `{program}`"));
+
+    // Write the input from another thread, so the program can't block on a full stdout pipe.
+    let mut stdin = proc.stdin.take().unwrap();
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = proc
+        .wait_with_output()
         .unwrap_or_else(|_| panic!("Failed to wait for program `{program}`"));
+    let _ = writer.join();
+    (
+        output.status,
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    )
+}
+
+/// Run the given command quietly in *cwd* and return its trimmed standard output if it succeeds.
+pub fn command_output(program: &str, args: &[&str], cwd: &Path) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// The outcome of a tool invocation, independent of the tool's specific exit codes.
+///
+/// Outcomes are ordered by severity, so the overall outcome of multiple invocations is their
+/// maximum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    /// The tool ran successfully and found no problems.
+    Success,
+
+    /// The tool ran successfully, but found problems (e.g. lint errors or failing tests).
+    Failure,
+
+    /// The tool did not run to completion (e.g. it crashed or was invoked incorrectly).
+    Error,
+}
+
+impl Outcome {
+    /// Returns the exit code that Tire exits with for this outcome.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Success => 0,
+            Outcome::Failure => 1,
+            Outcome::Error => 2,
+        }
+    }
+
+    /// Exit the process with the exit code for this outcome.
+    pub fn exit(self) -> ! {
+        std::process::exit(self.exit_code())
+    }
+}
+
+/// Maps the exit codes of a tool to an [Outcome]. Exit codes that are not mapped explicitly are
+/// treated as [Outcome::Error].
+#[derive(Clone, Debug)]
+pub struct ExitCodes {
+    tool: &'static str,
+    codes: Vec<(i32, Outcome)>,
+}
+
+impl ExitCodes {
+    /// Create a new mapping for the named tool, mapping only exit code 0 to [Outcome::Success].
+    pub fn new(tool: &'static str) -> Self {
+        Self {
+            tool,
+            codes: vec![(0, Outcome::Success)],
+        }
+    }
+
+    /// Exit codes of `ruff`: 1 if violations were found, 2 on abnormal termination.
+    pub fn ruff() -> Self {
+        Self::new("ruff").with(1, Outcome::Failure)
+    }
+
+    /// Exit codes of `mypy` and `dmypy`: 1 if type errors were found, 2 on crashes and usage
+    /// errors.
+    pub fn mypy() -> Self {
+        Self::new("mypy").with(1, Outcome::Failure)
+    }
+
+    /// Exit codes of `pytest`: 1 if tests failed, 5 if no tests were collected. 2 (interrupted),
+    /// 3 (internal error) and 4 (usage error) are errors.
+    pub fn pytest() -> Self {
+        Self::new("pytest")
+            .with(1, Outcome::Failure)
+            .with(5, Outcome::Failure)
+    }
+
+    /// Exit codes of `coverage report`: 2 if the total coverage is below `fail_under`, 1 on
+    /// errors.
+    pub fn coverage() -> Self {
+        Self::new("coverage").with(2, Outcome::Failure)
+    }
+
+    /// Map the given exit code to the given outcome, replacing any previous mapping.
+    pub fn with(mut self, code: i32, outcome: Outcome) -> Self {
+        self.codes.retain(|(c, _)| *c != code);
+        self.codes.push((code, outcome));
+        self
+    }
+
+    /// Returns the outcome for the given exit code. A process that was terminated by a signal has
+    /// no exit code and is treated as [Outcome::Error].
+    pub fn outcome(&self, code: Option<i32>) -> Outcome {
+        code.and_then(|code| {
+            self.codes
+                .iter()
+                .find(|(c, _)| *c == code)
+                .map(|(_, outcome)| *outcome)
+        })
+        .unwrap_or(Outcome::Error)
+    }
+}
+
+/// Run the given command and map its exit code to an [Outcome]. If the tool did not run to
+/// completion, a message is printed to stderr.
+pub fn run_tool(command: Vec<String>, exit_codes: &ExitCodes) -> Outcome {
+    let status = run_command(command);
+    let outcome = exit_codes.outcome(status.code());
+    if outcome == Outcome::Error {
+        match status.code() {
+            Some(code) => eprintln!("[tire] `{}` failed with exit code {code}", exit_codes.tool),
+            None => eprintln!("[tire] `{}` was terminated by a signal", exit_codes.tool),
+        }
+    }
+    outcome
+}
+
+/// Run the given command. If the command exits with a non-zero status code, print to stderr
+/// and exit the process.
+pub fn run_command_or_exit(command: Vec<String>) {
+    let program = command[0].clone();
+    let status = run_command(command);
     if !status.success() {
         let code = status.code().unwrap();
         eprintln!("Command `{program}` exited with code {code}");
@@ -47,3 +220,81 @@ pub fn find_pyproject_toml(cwd: Option<PathBuf>) -> Option<PathBuf> {
         }
     }
 }
+
+/// Find the project root directory, i.e. the directory that contains the `pyproject.toml` file
+/// found by [find_pyproject_toml]. If there is no `pyproject.toml`, the given *cwd* (or the
+/// current working directory) is assumed to be the project root.
+pub fn find_project_root(cwd: Option<PathBuf>) -> std::io::Result<PathBuf> {
+    match find_pyproject_toml(cwd.clone()) {
+        Some(file) => Ok(file.parent().unwrap().to_path_buf()),
+        None => cwd.map(Ok).unwrap_or_else(std::env::current_dir),
+    }
+}
+
+/// Resolves `.` and `..` components of *path* lexically, without touching the file system, e.g.
+/// `/project/app/../state` to `/project/state`.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Write *content* to *path* unless the file already has that content. The file is written to a
+/// temporary file first and then renamed, so that concurrent readers never see a partially
+/// written file. Returns `true` if the file was written.
+pub fn write_if_changed<C: AsRef<[u8]>>(path: &Path, content: C) -> std::io::Result<bool> {
+    let content = content.as_ref();
+    if std::fs::read(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    let temp = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+    std::fs::write(&temp, content)?;
+    if let Err(err) = std::fs::rename(&temp, path) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(err);
+    }
+    Ok(true)
+}
+
+/// Returns the hex-encoded SHA-256 digest of the given data.
+pub fn sha256_hex<D: AsRef<[u8]>>(data: D) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Match *text* against a shell-style wildcard *pattern* like Python's `fnmatch` does, where `*`
+/// matches any sequence of characters (including path separators) and `?` matches any single
+/// character.
+pub fn fnmatch(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((bp, bt)) = backtrack {
+            p = bp + 1;
+            t = bt + 1;
+            backtrack = Some((bp, bt + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
@@ -0,0 +1,227 @@
+//! Implements the `--watch` mode of the `tire test`, `tire check` and `tire lint` commands.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use ignore::{
+    Match, WalkBuilder,
+    gitignore::{Gitignore, GitignoreBuilder},
+};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    profile::state_dir,
+    select::is_python_file,
+    utils::{Outcome, find_project_root},
+};
+
+/// How long to wait for more changes after a change before rerunning the workflow, so that
+/// saving multiple files at once triggers only a single run.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Decides which files and directories in a project are watched, following the same rules as Git:
+/// `.gitignore` files in the project and its parent directories up to the repository root,
+/// `.git/info/exclude` and the global excludes file.
+pub struct Filter {
+    project_root: PathBuf,
+    state_dir: PathBuf,
+
+    /// The ignore files, ordered by precedence: `.gitignore` files in deeper directories first,
+    /// then the repository's exclude file and the global excludes file.
+    ignores: Vec<Gitignore>,
+}
+
+impl Filter {
+    pub fn new(project_root: &Path) -> Self {
+        let state_dir = state_dir(project_root);
+        let mut ignores: Vec<Gitignore> = Vec::new();
+
+        // The `.gitignore` files of the parent directories apply up to the repository root.
+        let repo_root = project_root.ancestors().find(|x| x.join(".git").exists());
+        for dir in project_root.ancestors().skip(1) {
+            if !repo_root.is_some_and(|root| dir.starts_with(root)) {
+                break;
+            }
+            ignores.push(Gitignore::new(dir.join(".gitignore")).0);
+        }
+        for dir in Self::walk(project_root, &state_dir) {
+            let file = dir.join(".gitignore");
+            if file.is_file() {
+                ignores.push(Gitignore::new(file).0);
+            }
+        }
+        ignores.sort_by_key(|x| std::cmp::Reverse(x.path().components().count()));
+
+        // Patterns in these files are relative to the repository root, not to their directory.
+        let root = repo_root.unwrap_or(project_root);
+        let mut exclude = GitignoreBuilder::new(root);
+        exclude.add(root.join(".git/info/exclude"));
+        ignores.extend(exclude.build().ok());
+        ignores.push(GitignoreBuilder::new(root).build_global().0);
+
+        Self {
+            project_root: project_root.to_path_buf(),
+            state_dir,
+            ignores,
+        }
+    }
+
+    /// Returns the directories in *root* that aren't hidden or ignored, including *root*.
+    fn walk(root: &Path, state_dir: &Path) -> Vec<PathBuf> {
+        let state_dir = state_dir.to_path_buf();
+        WalkBuilder::new(root)
+            .require_git(false)
+            .filter_entry(move |entry| !entry.path().starts_with(&state_dir))
+            .build()
+            .filter_map(Result::ok)
+            .filter(|x| x.file_type().is_some_and(|x| x.is_dir()))
+            .map(|x| x.into_path())
+            .collect()
+    }
+
+    /// Returns the directories to watch for changes below *dir*, including *dir*.
+    pub fn directories(&self, dir: &Path) -> Vec<PathBuf> {
+        if !self.is_watched(dir, true) {
+            return Vec::new();
+        }
+        Self::walk(dir, &self.state_dir)
+            .into_iter()
+            .filter(|x| !self.is_ignored(x, true))
+            .collect()
+    }
+
+    /// Checks if the given path is ignored by any of the ignore files.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for ignore in &self.ignores {
+            if !path.starts_with(ignore.path()) {
+                continue;
+            }
+            match ignore.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    /// Checks if the given path is in the project, not ignored, not in the state directory and not
+    /// in a hidden directory (such as `.git/` or `.venv/`).
+    fn is_watched(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.project_root) else {
+            return false;
+        };
+        !path.starts_with(&self.state_dir)
+            && !relative
+                .components()
+                .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+            && !self.is_ignored(path, is_dir)
+    }
+
+    /// Checks if a change to the given file should trigger a rerun: it must be a Python file or the
+    /// `pyproject.toml` in the project that is watched.
+    pub fn is_relevant(&self, path: &Path) -> bool {
+        (is_python_file(path) || path == self.project_root.join("pyproject.toml"))
+            && self.is_watched(path, false)
+    }
+}
+
+/// Run *workflow* once, then again whenever Python files in the project change, until the process
+/// is interrupted. The workflow receives the changed files relative to the project root, which is
+/// empty for the first run.
+pub fn watch<F: FnMut(&[PathBuf]) -> Outcome>(mut workflow: F) -> ! {
+    let project_root = find_project_root(None).unwrap();
+    let mut filter = Filter::new(&project_root);
+
+    // Directories are watched individually, so that ignored directories such as a virtual
+    // environment don't use up the system's limit of watches.
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).unwrap();
+    for dir in filter.directories(&project_root) {
+        watch_dir(&mut watcher, &dir);
+    }
+
+    let mut changed: Vec<PathBuf> = Vec::new();
+    let mut run = 0;
+    loop {
+        run += 1;
+        clear_screen();
+        if !changed.is_empty() {
+            let names: Vec<String> = changed.iter().map(|x| x.display().to_string()).collect();
+            eprintln!("[tire] Changed: {}", names.join(", "));
+        }
+
+        let outcome = workflow(&changed);
+        let status = match outcome {
+            Outcome::Success => "succeeded",
+            Outcome::Failure => "found problems",
+            Outcome::Error => "failed to run",
+        };
+        eprintln!("[tire] Run #{run} {status}. Waiting for changes ...");
+
+        changed = wait_for_changes(&rx, &mut watcher, &mut filter);
+    }
+}
+
+fn watch_dir(watcher: &mut RecommendedWatcher, dir: &Path) {
+    if let Err(err) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+        log::warn!("Failed to watch {}: {err}", dir.display());
+    }
+}
+
+/// Block until relevant files changed and no further changes happened for [DEBOUNCE]. Returns
+/// the changed files relative to the project root. New directories are watched as well, and the
+/// *filter* is updated when an ignore file changes.
+fn wait_for_changes(
+    rx: &Receiver<notify::Result<notify::Event>>,
+    watcher: &mut RecommendedWatcher,
+    filter: &mut Filter,
+) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = Vec::new();
+    loop {
+        let event = if changed.is_empty() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(DEBOUNCE)
+        };
+        let event = match event {
+            Ok(Ok(event)) => event,
+            Ok(Err(err)) => {
+                log::warn!("Error while watching files: {err}");
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => return changed,
+            Err(RecvTimeoutError::Disconnected) => panic!("File watcher stopped unexpectedly"),
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            continue;
+        }
+        for path in event.paths {
+            if path.file_name().is_some_and(|x| x == ".gitignore") {
+                *filter = Filter::new(&filter.project_root);
+            }
+            if matches!(event.kind, EventKind::Create(_)) && path.is_dir() {
+                for dir in filter.directories(&path) {
+                    watch_dir(watcher, &dir);
+                }
+            }
+            if filter.is_relevant(&path) {
+                let relative = path
+                    .strip_prefix(&filter.project_root)
+                    .unwrap()
+                    .to_path_buf();
+                if !changed.contains(&relative) {
+                    changed.push(relative);
+                }
+            }
+        }
+    }
+}
+
+/// Clear the terminal screen and move the cursor to the top left corner.
+fn clear_screen() {
+    eprint!("\x1b[2J\x1b[H");
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::path::Path;
+use tire::baseline::{Baseline, Key, checked_paths, key};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::diagnostic;
+
+    #[test]
+    fn test_key_is_robust_to_line_shifts() {
+        let a = key(&diagnostic(3, "F401"), "src/pkg/mod.py", "import os");
+        let b = key(&diagnostic(10, "F401"), "src/pkg/mod.py", "    import os  ");
+        let c = key(&diagnostic(3, "F401"), "src/pkg/mod.py", "import sys");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_compare() {
+        let diagnostics = vec![
+            diagnostic(1, "F401"),
+            diagnostic(2, "F401"),
+            diagnostic(3, "E501"),
+        ];
+        let keys: Vec<Key> = diagnostics
+            .iter()
+            .map(|x| key(x, &x.file, "import os"))
+            .collect();
+
+        // One of the two F401 violations and the E501 violation are in the baseline.
+        let mut baseline = Baseline::default();
+        baseline.update("ruff", &[keys[0].clone(), keys[2].clone()], None);
+        let comparison = baseline.compare("ruff", diagnostics.clone(), &keys, None);
+        assert_eq!(comparison.new, vec![diagnostic(2, "F401")]);
+        assert_eq!(comparison.suppressed, 2);
+        assert!(comparison.fixed.is_empty());
+
+        // The E501 violation was fixed.
+        let comparison = baseline.compare("ruff", diagnostics[..2].to_vec(), &keys[..2], None);
+        assert_eq!(comparison.new, vec![diagnostic(2, "F401")]);
+        assert_eq!(comparison.fixed.len(), 1);
+        assert_eq!(comparison.fixed[0].key.code, "E501");
+        assert_eq!(comparison.fixed[0].count, 1);
+
+        // Entries of other tools are not affected by updates, nor used for comparison.
+        baseline.update("mypy", &[], None);
+        assert_eq!(baseline.entries.len(), 2);
+        let comparison = baseline.compare("mypy", vec![], &[], None);
+        assert!(comparison.fixed.is_empty());
+    }
+
+    #[test]
+    fn test_partial_run() {
+        let diagnostics = vec![diagnostic(1, "F401"), diagnostic(2, "F401")];
+        let keys = vec![
+            key(&diagnostics[0], "src/pkg/mod.py", "import os"),
+            key(&diagnostics[1], "src/other.py", "import os"),
+        ];
+        let mut baseline = Baseline::default();
+        baseline.update("ruff", &keys, None);
+
+        // Only `src/pkg/` was checked, so the entry of `src/other.py` is not fixed.
+        let checked = vec!["src/pkg".to_owned()];
+        let comparison = baseline.compare("ruff", vec![], &[], Some(&checked));
+        assert_eq!(comparison.fixed.len(), 1);
+        assert_eq!(comparison.fixed[0].key.file, "src/pkg/mod.py");
+
+        // Updating the baseline keeps the entries of files that were not checked.
+        baseline.update("ruff", &[], Some(&checked));
+        assert_eq!(baseline.entries.len(), 1);
+        assert_eq!(baseline.entries[0].key.file, "src/other.py");
+    }
+
+    #[test]
+    fn test_checked_paths() {
+        let project_root = Path::new("/project");
+        let files = |files: &[&str]| files.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!(checked_paths(project_root, project_root, &[]), None);
+        assert_eq!(
+            checked_paths(project_root, project_root, &files(&["."])),
+            None
+        );
+        assert_eq!(
+            checked_paths(
+                project_root,
+                &project_root.join("src"),
+                &files(&[".", "../tests/test_a.py", "/elsewhere/b.py"])
+            ),
+            Some(files(&["src", "tests/test_a.py"]))
+        );
+    }
+}
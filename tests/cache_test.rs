@@ -0,0 +1,51 @@
+use std::path::Path;
+use tire::cache::{project_args, run_key};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_key() {
+        let args = vec![".".to_owned()];
+        let files = vec![("app.py".to_owned(), "abc".to_owned())];
+        let key = run_key("ruff", "ruff 0.8.0", "cfg", &args, &files);
+
+        assert_eq!(key, run_key("ruff", "ruff 0.8.0", "cfg", &args, &files));
+        assert_ne!(key, run_key("mypy", "ruff 0.8.0", "cfg", &args, &files));
+        assert_ne!(key, run_key("ruff", "ruff 0.8.1", "cfg", &args, &files));
+        assert_ne!(key, run_key("ruff", "ruff 0.8.0", "other", &args, &files));
+        assert_ne!(
+            key,
+            run_key("ruff", "ruff 0.8.0", "cfg", &["app.py".to_owned()], &files)
+        );
+        let changed = vec![("app.py".to_owned(), "def".to_owned())];
+        assert_ne!(key, run_key("ruff", "ruff 0.8.0", "cfg", &args, &changed));
+    }
+
+    #[test]
+    fn test_project_args() {
+        let root = Path::new("/project");
+        let args = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+        // The default `.` in a subdirectory must not have the same key as in the project root.
+        assert_eq!(project_args(root, root, &[]), vec!["."]);
+        assert_eq!(project_args(root, &root.join("pkg"), &[]), vec!["pkg"]);
+        assert_eq!(
+            project_args(root, &root.join("pkg"), &args(&["."])),
+            vec!["pkg"]
+        );
+        assert_eq!(
+            project_args(root, &root.join("pkg"), &args(&["./a.py", "../tests"])),
+            vec!["pkg/a.py", "tests"]
+        );
+        assert_eq!(
+            project_args(root, root, &args(&["pkg/a.py"])),
+            project_args(root, &root.join("pkg"), &args(&["a.py"]))
+        );
+        assert_eq!(
+            project_args(root, root, &args(&["../other"])),
+            vec!["/other"]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::time::Duration;
+use tire::{
+    ci::{Completed, LogGroups, STEPS, overall_outcome, sort_by_step},
+    utils::Outcome,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_groups() {
+        assert_eq!(LogGroups::GitHub.start("lint", 10), "::group::lint");
+        assert_eq!(
+            LogGroups::GitHub.end("lint", 20).as_deref(),
+            Some("::endgroup::")
+        );
+    }
+
+    #[test]
+    fn test_gitlab_groups() {
+        assert_eq!(
+            LogGroups::GitLab.start("test", 10),
+            "\x1b[0Ksection_start:10:test\r\x1b[0Ktire test"
+        );
+        assert_eq!(
+            LogGroups::GitLab.end("test", 20).as_deref(),
+            Some("\x1b[0Ksection_end:20:test\r\x1b[0K")
+        );
+        assert_eq!(LogGroups::Plain.end("test", 20), None);
+    }
+
+    fn completed(index: usize, outcome: Outcome) -> Completed {
+        Completed {
+            step: STEPS[index],
+            outcome,
+            duration: Duration::from_secs(1),
+            output: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_by_step() {
+        let mut results = vec![
+            completed(3, Outcome::Success),
+            completed(0, Outcome::Success),
+            completed(2, Outcome::Success),
+            completed(1, Outcome::Success),
+        ];
+        sort_by_step(&mut results);
+        let names: Vec<&str> = results.iter().map(|x| x.step.name).collect();
+        assert_eq!(names, vec!["fmt", "lint", "check", "test"]);
+    }
+
+    #[test]
+    fn test_overall_outcome() {
+        assert_eq!(overall_outcome(&[]), Outcome::Success);
+        assert_eq!(
+            overall_outcome(&[
+                completed(0, Outcome::Success),
+                completed(1, Outcome::Success)
+            ]),
+            Outcome::Success
+        );
+        assert_eq!(
+            overall_outcome(&[
+                completed(0, Outcome::Failure),
+                completed(1, Outcome::Success)
+            ]),
+            Outcome::Failure
+        );
+        assert_eq!(
+            overall_outcome(&[
+                completed(0, Outcome::Failure),
+                completed(1, Outcome::Error),
+                completed(2, Outcome::Success)
+            ]),
+            Outcome::Error
+        );
+    }
+}
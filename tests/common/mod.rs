@@ -0,0 +1,44 @@
+//! Helpers shared by the integration tests. Not every test uses every helper.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use tire::{
+    diagnostic::{Diagnostic, Severity},
+    select::git,
+};
+
+/// Creates an empty Git repository with a `main` branch in a temporary directory.
+pub fn repo(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tire-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir = dir.canonicalize().unwrap();
+    git(&dir, &["init", "-q"]).unwrap();
+    git(&dir, &["symbolic-ref", "HEAD", "refs/heads/main"]).unwrap();
+    git(&dir, &["config", "user.name", "Tire"]).unwrap();
+    git(&dir, &["config", "user.email", "tire@example.com"]).unwrap();
+    git(&dir, &["config", "commit.gpgsign", "false"]).unwrap();
+    dir
+}
+
+/// Commits all changes in the repository.
+pub fn commit(dir: &Path, message: &str) {
+    git(dir, &["add", "-A"]).unwrap();
+    git(dir, &["commit", "-q", "-m", message]).unwrap();
+}
+
+/// Returns a Ruff diagnostic with the given *line* and *code* in `src/pkg/mod.py`.
+pub fn diagnostic(line: usize, code: &str) -> Diagnostic {
+    Diagnostic {
+        tool: "ruff".to_owned(),
+        file: "src/pkg/mod.py".to_owned(),
+        line,
+        column: 1,
+        end_line: None,
+        end_column: None,
+        code: Some(code.to_owned()),
+        message: "message".to_owned(),
+        severity: Severity::Error,
+    }
+}
@@ -0,0 +1,111 @@
+use tire::diagnostic::{
+    Diagnostic, OutputFormat, Severity, parse_mypy_output, parse_ruff_json, render,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic() -> Diagnostic {
+        Diagnostic {
+            tool: "ruff".to_owned(),
+            file: "src/pkg/mod.py".to_owned(),
+            line: 3,
+            column: 8,
+            end_line: Some(3),
+            end_column: Some(10),
+            code: Some("F401".to_owned()),
+            message: "`os` imported but unused, 100%".to_owned(),
+            severity: Severity::Error,
+        }
+    }
+
+    #[test]
+    fn test_parse_ruff_json() {
+        let json = r#"[
+            {
+                "cell": null,
+                "code": "F401",
+                "end_location": {"column": 10, "row": 3},
+                "filename": "src/pkg/mod.py",
+                "fix": null,
+                "location": {"column": 8, "row": 3},
+                "message": "`os` imported but unused, 100%",
+                "noqa_row": 3,
+                "url": "https://docs.astral.sh/ruff/rules/unused-import"
+            }
+        ]"#;
+        assert_eq!(parse_ruff_json(json).unwrap(), vec![diagnostic()]);
+    }
+
+    #[test]
+    fn test_parse_mypy_output() {
+        let output = r#"src/pkg/mod.py: note: In function "main":
+src/pkg/mod.py:12:5: error: Incompatible return value type (got "int", expected "str")  [return-value]
+src/pkg/mod.py:14: note: See https://mypy.readthedocs.io/en/stable/
+Found 1 error in 1 file (checked 2 source files)
+"#;
+        let diagnostics = parse_mypy_output(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].file, "src/pkg/mod.py");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (12, 5));
+        assert_eq!(diagnostics[0].code.as_deref(), Some("return-value"));
+        assert_eq!(
+            diagnostics[0].message,
+            "Incompatible return value type (got \"int\", expected \"str\")"
+        );
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!((diagnostics[1].line, diagnostics[1].column), (14, 1));
+        assert_eq!(diagnostics[1].code, None);
+        assert_eq!(diagnostics[1].severity, Severity::Note);
+    }
+
+    #[test]
+    fn test_render_github() {
+        assert_eq!(
+            render(OutputFormat::Github, &[diagnostic()]),
+            "::error file=src/pkg/mod.py,line=3,col=8,endLine=3,endColumn=10,title=ruff (F401)::`os` imported but unused, 100%25\n"
+        );
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(
+            render(OutputFormat::Text, &[diagnostic()]),
+            "src/pkg/mod.py:3:8: F401 `os` imported but unused, 100%\n"
+        );
+    }
+
+    #[test]
+    fn test_render_sarif() {
+        let sarif: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Sarif, &[diagnostic()])).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["name"], "ruff");
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "F401");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            3
+        );
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let junit = render(OutputFormat::Junit, &[diagnostic()]);
+        assert!(junit.contains(r#"<testsuites name="tire" tests="1" failures="1" errors="0">"#));
+        assert!(
+            junit.contains(r#"<failure message="`os` imported but unused, 100%" type="F401">"#)
+        );
+    }
+
+    #[test]
+    fn test_render_gitlab() {
+        let gitlab: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Gitlab, &[diagnostic()])).unwrap();
+        assert_eq!(gitlab[0]["check_name"], "ruff/F401");
+        assert_eq!(gitlab[0]["location"]["path"], "src/pkg/mod.py");
+        assert_eq!(gitlab[0]["location"]["lines"]["begin"], 3);
+        assert_eq!(gitlab[0]["severity"], "major");
+    }
+}
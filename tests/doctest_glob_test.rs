@@ -0,0 +1,39 @@
+use tire::test::{matches_doctest_glob, pytest_glob};
+use tire::utils::fnmatch;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnmatch() {
+        assert!(fnmatch("*.md", "README.md"));
+        assert!(fnmatch("*.md", "docs/guide/intro.md"));
+        assert!(fnmatch("test?.txt", "test1.txt"));
+        assert!(fnmatch("*", ""));
+        assert!(!fnmatch("*.md", "README.rst"));
+        assert!(!fnmatch("test?.txt", "test.txt"));
+    }
+
+    #[test]
+    fn test_pytest_glob() {
+        assert_eq!(pytest_glob("docs/**/*.md"), "docs/*.md");
+        assert_eq!(pytest_glob("**/*.rst"), "*.rst");
+        assert_eq!(pytest_glob("README.md"), "README.md");
+    }
+
+    #[test]
+    fn test_matches_doctest_glob() {
+        assert!(matches_doctest_glob("README.md", "README.md"));
+        assert!(matches_doctest_glob("README.md", "packages/foo/README.md"));
+        assert!(matches_doctest_glob("docs/*.md", "docs/index.md"));
+        assert!(matches_doctest_glob("docs/*.md", "docs/guide/intro.md"));
+        assert!(matches_doctest_glob(
+            "docs/*.md",
+            "packages/foo/docs/index.md"
+        ));
+        assert!(matches_doctest_glob("*.rst", "docs/index.rst"));
+        assert!(!matches_doctest_glob("docs/*.md", "mydocs/index.md"));
+        assert!(!matches_doctest_glob("README.md", "CHANGELOG.md"));
+    }
+}
@@ -0,0 +1,40 @@
+use tire::{
+    doctor::{Check, Status, outcome},
+    utils::Outcome,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(status: Status, fix: Option<&str>) -> Check {
+        Check {
+            name: "uv".to_owned(),
+            status,
+            detail: "uv 0.7.3".to_owned(),
+            fix: fix.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(check(Status::Pass, None).to_string(), "[PASS] uv: uv 0.7.3");
+        assert_eq!(
+            check(Status::Warn, Some("upgrade uv")).to_string(),
+            "[WARN] uv: uv 0.7.3\n       fix: upgrade uv"
+        );
+    }
+
+    #[test]
+    fn test_outcome() {
+        assert_eq!(outcome(&[]), Outcome::Success);
+        assert_eq!(
+            outcome(&[check(Status::Pass, None), check(Status::Warn, Some("x"))]),
+            Outcome::Success
+        );
+        assert_eq!(
+            outcome(&[check(Status::Fail, Some("x")), check(Status::Pass, None)]),
+            Outcome::Failure
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use serde_json::json;
+use tire::editor::{has_toml_comments, merge_helix, merge_json, vscode_settings};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_vscode_settings() {
+        let mut settings = json!({
+            "editor.rulers": [120],
+            "ruff.configuration": "pyproject.toml",
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+
+        let changed = merge_json(&mut settings, vscode_settings(".tire/pyproject.toml"));
+        assert!(changed.contains(&"ruff.configuration".to_owned()));
+        assert_eq!(settings["editor.rulers"], json!([120]));
+        assert_eq!(
+            settings["ruff.configuration"],
+            json!("${workspaceFolder}/.tire/pyproject.toml")
+        );
+
+        // Merging again changes nothing.
+        assert!(merge_json(&mut settings, vscode_settings(".tire/pyproject.toml")).is_empty());
+    }
+
+    #[test]
+    fn test_merge_vscode_args() {
+        let mut settings = json!({
+            "mypy-type-checker.args": ["--strict", "--config-file=pyproject.toml"],
+            "python.testing.pytestArgs": ["-x", "-c", "pyproject.toml", "--rootdir=.", "tests"],
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+
+        merge_json(&mut settings, vscode_settings(".tire/pyproject.toml"));
+        assert_eq!(
+            settings["mypy-type-checker.args"],
+            json!([
+                "--config-file=${workspaceFolder}/.tire/pyproject.toml",
+                "--strict"
+            ])
+        );
+        assert_eq!(
+            settings["python.testing.pytestArgs"],
+            json!([
+                "-c",
+                "${workspaceFolder}/.tire/pyproject.toml",
+                "--rootdir",
+                "${workspaceFolder}",
+                "-x",
+                "tests"
+            ])
+        );
+
+        // Merging again changes nothing.
+        assert!(merge_json(&mut settings, vscode_settings(".tire/pyproject.toml")).is_empty());
+    }
+
+    #[test]
+    fn test_merge_helix() {
+        let mut languages: toml::Table = toml::from_str(
+            r#"
+            [[language]]
+            name = "rust"
+            auto-format = true
+
+            [[language]]
+            name = "python"
+            language-servers = ["pylsp"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(merge_helix(&mut languages, ".tire/pyproject.toml"));
+        assert_eq!(
+            languages["language-server"]["ruff"]["config"]["settings"]["configuration"].as_str(),
+            Some(".tire/pyproject.toml")
+        );
+        let entries = languages["language"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["name"].as_str(), Some("rust"));
+        let servers: Vec<&str> = entries[1]["language-servers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|x| x.as_str())
+            .collect();
+        assert_eq!(servers, vec!["pylsp", "ruff"]);
+        assert_eq!(entries[1]["formatter"]["command"].as_str(), Some("tire"));
+
+        assert!(!merge_helix(&mut languages, ".tire/pyproject.toml"));
+
+        // Without a Python entry, Helix's default language servers are kept.
+        let mut languages = toml::Table::new();
+        assert!(merge_helix(&mut languages, ".tire/pyproject.toml"));
+        let servers: Vec<&str> = languages["language"][0]["language-servers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|x| x.as_str())
+            .collect();
+        assert_eq!(servers, vec!["ruff", "jedi", "pylsp"]);
+    }
+
+    #[test]
+    fn test_has_toml_comments() {
+        assert!(has_toml_comments("# comment\n[[language]]\n"));
+        assert!(has_toml_comments("name = \"python\" # comment\n"));
+        assert!(has_toml_comments("a = 'x' # \"quoted\"\n"));
+        assert!(!has_toml_comments("name = \"#python\"\n"));
+        assert!(!has_toml_comments("name = \"\\\"#\"\n"));
+        assert!(!has_toml_comments("args = ['#', \"\"\"\n#\n\"\"\"]\n"));
+    }
+}
@@ -0,0 +1,37 @@
+use tire::utils::{ExitCodes, Outcome};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pytest_exit_codes() {
+        let exit_codes = ExitCodes::pytest();
+        assert_eq!(exit_codes.outcome(Some(0)), Outcome::Success);
+        assert_eq!(exit_codes.outcome(Some(1)), Outcome::Failure);
+        assert_eq!(exit_codes.outcome(Some(3)), Outcome::Error);
+        assert_eq!(exit_codes.outcome(Some(5)), Outcome::Failure);
+        assert_eq!(exit_codes.outcome(None), Outcome::Error);
+
+        let exit_codes = exit_codes.with(5, Outcome::Success);
+        assert_eq!(exit_codes.outcome(Some(5)), Outcome::Success);
+    }
+
+    #[test]
+    fn test_coverage_exit_codes() {
+        let exit_codes = ExitCodes::coverage();
+        assert_eq!(exit_codes.outcome(Some(0)), Outcome::Success);
+        assert_eq!(exit_codes.outcome(Some(1)), Outcome::Error);
+        assert_eq!(exit_codes.outcome(Some(2)), Outcome::Failure);
+    }
+
+    #[test]
+    fn test_outcome_ordering() {
+        assert_eq!(
+            [Outcome::Failure, Outcome::Success].into_iter().max(),
+            Some(Outcome::Failure)
+        );
+        assert_eq!(Outcome::Error.max(Outcome::Failure), Outcome::Error);
+        assert_eq!(Outcome::Error.exit_code(), 2);
+    }
+}
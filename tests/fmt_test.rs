@@ -0,0 +1,66 @@
+use std::process::Command;
+use tire::{
+    fmt::{format_code, format_files, selects_import_rules},
+    profile::Profile,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selects(config: &str) -> bool {
+        selects_import_rules(&toml::from_str(config).unwrap())
+    }
+
+    #[test]
+    fn test_selects_import_rules() {
+        assert!(selects("[tool.ruff.lint]\nselect = [\"E\", \"F\", \"I\"]"));
+        assert!(selects("[tool.ruff.lint]\nselect = [\"I001\"]"));
+        assert!(selects("[tool.ruff.lint]\nselect = [\"ALL\"]"));
+        assert!(selects("[tool.ruff.lint]\nextend-select = [\"I\"]"));
+        assert!(selects("[tool.ruff]\nselect = [\"I\"]"));
+
+        // Ruff's default selection does not include the `I` rules.
+        assert!(!selects(""));
+        assert!(!selects("[tool.ruff.lint]\nselect = [\"E\", \"F\"]"));
+        assert!(!selects("[tool.ruff.lint]\nselect = [\"I002\"]"));
+        assert!(!selects(
+            "[tool.ruff.lint]\nselect = [\"ALL\"]\nignore = [\"I\"]"
+        ));
+    }
+
+    #[test]
+    fn test_format_files_and_code_agree() {
+        // Skip the test if uv or Ruff isn't available.
+        let ruff = Command::new("uv")
+            .args(["run", "--no-project", "--with", "ruff", "ruff", "--version"])
+            .output();
+        if !ruff.is_ok_and(|x| x.status.success()) {
+            return;
+        }
+
+        let dir = std::env::temp_dir().join(format!("tire-fmt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("pyproject.toml"),
+            "[tool.ruff]\nline-length = 30\n\n[tool.tire.fmt]\nsort-imports = true\n",
+        )
+        .unwrap();
+        let materialized = Profile::load(Some("default".to_owned()))
+            .unwrap()
+            .materialize(Some(dir.clone()))
+            .unwrap();
+
+        // The sorted import is too long, so the formatter has to wrap it.
+        let code = "from pkg import zeta, alpha, beta, gamma\nimport os\nx = [1,2,\n3]\n";
+        let file = dir.join("mod.py");
+        std::fs::write(&file, code).unwrap();
+        let file = file.to_string_lossy().to_string();
+
+        let formatted = format_code(&materialized, &file, code).unwrap();
+        format_files(&materialized, &[file.clone()], false, false);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), formatted);
+        assert_ne!(formatted, code);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
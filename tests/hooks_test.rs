@@ -0,0 +1,111 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use tire::{
+    hooks::{Unstaged, hook_script, is_tire_hook},
+    select::git,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_tire_hook() {
+        assert!(is_tire_hook(&hook_script()));
+        assert!(!is_tire_hook("#!/bin/sh\nexec pre-commit run\n"));
+    }
+
+    /// Creates a Git repository with a committed `a.py` and `pkg/b.py` in a temporary directory.
+    fn repo(name: &str) -> PathBuf {
+        let dir = common::repo(name);
+        std::fs::create_dir_all(dir.join("pkg")).unwrap();
+        std::fs::write(dir.join("a.py"), "x = 1\n").unwrap();
+        std::fs::write(dir.join("pkg/b.py"), "y = 1\n").unwrap();
+        common::commit(&dir, "init");
+        dir
+    }
+
+    fn read(dir: &Path, file: &str) -> String {
+        std::fs::read_to_string(dir.join(file)).unwrap()
+    }
+
+    #[test]
+    fn test_unstaged_set_aside_and_restore() {
+        let dir = repo("hooks-unstaged");
+
+        // `a.py` has staged and unstaged changes, `pkg/b.py` only unstaged ones.
+        std::fs::write(dir.join("a.py"), "x = 2\n").unwrap();
+        git(&dir, &["add", "a.py"]).unwrap();
+        std::fs::write(dir.join("a.py"), "x = 3\n").unwrap();
+        std::fs::write(dir.join("pkg/b.py"), "y = 2\n").unwrap();
+        std::fs::write(dir.join("untracked.py"), "z = 1\n").unwrap();
+
+        // Setting aside works from a subdirectory of the repository, too.
+        let unstaged = Unstaged::set_aside(&dir.join("pkg")).unwrap();
+        assert!(unstaged.is_some());
+        assert_eq!(read(&dir, "a.py"), "x = 2\n");
+        assert_eq!(read(&dir, "pkg/b.py"), "y = 1\n");
+        assert_eq!(read(&dir, "untracked.py"), "z = 1\n");
+
+        drop(unstaged);
+        assert_eq!(read(&dir, "a.py"), "x = 3\n");
+        assert_eq!(read(&dir, "pkg/b.py"), "y = 2\n");
+        assert_eq!(git(&dir, &["show", ":a.py"]).unwrap(), "x = 2\n");
+        assert_eq!(
+            git(&dir, &["diff", "--cached", "--name-only"]).unwrap(),
+            "a.py\n"
+        );
+        let patch = git(&dir, &["rev-parse", "--git-path", "tire-unstaged.patch"]).unwrap();
+        assert!(!dir.join(patch.trim()).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unstaged_nothing_to_set_aside() {
+        let dir = repo("hooks-clean");
+        std::fs::write(dir.join("a.py"), "x = 2\n").unwrap();
+        git(&dir, &["add", "a.py"]).unwrap();
+
+        assert!(Unstaged::set_aside(&dir).unwrap().is_none());
+        assert_eq!(read(&dir, "a.py"), "x = 2\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unstaged_keeps_non_utf8_changes() {
+        let dir = repo("hooks-latin1");
+        std::fs::write(dir.join("data.bin"), [0u8, 1, 2]).unwrap();
+        git(&dir, &["add", "data.bin"]).unwrap();
+        git(&dir, &["commit", "-q", "-m", "data"]).unwrap();
+
+        // A Latin-1 text change and a binary change.
+        std::fs::write(dir.join("a.py"), b"# caf\xe9\n").unwrap();
+        std::fs::write(dir.join("data.bin"), [0u8, 0xff, 0xfe, 0x80]).unwrap();
+
+        let unstaged = Unstaged::set_aside(&dir).unwrap();
+        assert!(unstaged.is_some());
+        assert_eq!(read(&dir, "a.py"), "x = 1\n");
+        drop(unstaged);
+        assert_eq!(std::fs::read(dir.join("a.py")).unwrap(), b"# caf\xe9\n");
+        assert_eq!(
+            std::fs::read(dir.join("data.bin")).unwrap(),
+            [0u8, 0xff, 0xfe, 0x80]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unstaged_refuses_to_overwrite_patch() {
+        let dir = repo("hooks-leftover");
+        let patch = git(&dir, &["rev-parse", "--git-path", "tire-unstaged.patch"]).unwrap();
+        let patch = dir.join(patch.trim());
+        std::fs::write(&patch, "earlier work").unwrap();
+        std::fs::write(dir.join("a.py"), "x = 2\n").unwrap();
+
+        assert!(Unstaged::set_aside(&dir).is_err());
+        assert_eq!(std::fs::read_to_string(&patch).unwrap(), "earlier work");
+        assert_eq!(read(&dir, "a.py"), "x = 2\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,50 @@
+use tire::init::{
+    ProjectKind, is_valid_package_name, missing_gitignore_lines, package_name, pyproject_toml,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("My-Project.io"), "my_project_io");
+    }
+
+    #[test]
+    fn test_is_valid_package_name() {
+        assert!(is_valid_package_name("my_project"));
+        assert!(is_valid_package_name("_private2"));
+        assert!(!is_valid_package_name("2fast"));
+        assert!(!is_valid_package_name("my_project!"));
+        assert!(!is_valid_package_name("class"));
+        assert!(!is_valid_package_name(""));
+    }
+
+    #[test]
+    fn test_pyproject_toml() {
+        let lib: toml::Table = toml::from_str(&pyproject_toml("my-lib", ProjectKind::Lib)).unwrap();
+        assert_eq!(lib["project"]["name"].as_str(), Some("my-lib"));
+        assert_eq!(lib["tool"]["tire"]["profile"].as_str(), Some("default"));
+        assert!(lib["project"].get("scripts").is_none());
+
+        let app: toml::Table = toml::from_str(&pyproject_toml("my-app", ProjectKind::App)).unwrap();
+        assert_eq!(
+            app["project"]["scripts"]["my-app"].as_str(),
+            Some("my_app:main")
+        );
+
+        // Names are escaped, so that they can't break the TOML syntax.
+        let name = r#"a "quoted" \ name"#;
+        let app: toml::Table = toml::from_str(&pyproject_toml(name, ProjectKind::App)).unwrap();
+        assert_eq!(app["project"]["name"].as_str(), Some(name));
+        assert!(app["project"]["scripts"].get(name).is_some());
+    }
+
+    #[test]
+    fn test_missing_gitignore_lines() {
+        let missing =
+            missing_gitignore_lines("__pycache__/\n.venv/\n/dist/\n.coverage\n .tire/ \n");
+        assert_eq!(missing, vec![".dmypy.json"]);
+    }
+}
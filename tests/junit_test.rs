@@ -0,0 +1,113 @@
+use tire::junit::{Summary, TestOutcome, TestReport};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites name="pytest tests">
+  <testsuite name="pytest" errors="1" failures="1" skipped="2" tests="5" time="1.500" timestamp="2025-06-01T12:00:00" hostname="ci">
+    <testcase classname="tests.test_foo" name="test_pass" time="0.010" />
+    <testcase classname="tests.test_foo.TestFoo" name="test_fail[1]" time="0.750">
+      <failure message="assert 1 == 2">def test_fail(): ...</failure>
+    </testcase>
+    <testcase classname="tests.test_foo" name="test_skip" time="0.001">
+      <skipped type="pytest.skip" message="not on CI">skipped</skipped>
+    </testcase>
+    <testcase classname="tests.test_foo" name="test_xfail" time="0.020">
+      <skipped type="pytest.xfail" message="known bug &amp; tracked" />
+    </testcase>
+    <testcase classname="tests.test_bar" name="test_teardown" time="0.300">
+      <failure message="assert False" />
+      <error message="failed on teardown with &quot;RuntimeError&quot;" />
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+
+    #[test]
+    fn test_parse() {
+        let report = TestReport::parse(REPORT).unwrap();
+        assert_eq!(report.tests.len(), 5);
+        assert_eq!(report.duration, 1.5);
+
+        assert_eq!(report.tests[0].id(), "tests.test_foo::test_pass");
+        assert_eq!(report.tests[0].outcome, TestOutcome::Passed);
+        assert_eq!(report.tests[1].outcome, TestOutcome::Failed);
+        assert_eq!(report.tests[1].message.as_deref(), Some("assert 1 == 2"));
+        assert_eq!(report.tests[2].outcome, TestOutcome::Skipped);
+        assert_eq!(report.tests[3].outcome, TestOutcome::XFailed);
+        assert_eq!(
+            report.tests[3].message.as_deref(),
+            Some("known bug & tracked")
+        );
+        assert_eq!(report.tests[4].outcome, TestOutcome::Error);
+        assert_eq!(
+            report.tests[4].message.as_deref(),
+            Some("failed on teardown with \"RuntimeError\"")
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let report = TestReport::parse(REPORT).unwrap();
+        let summary = report.summary();
+        assert_eq!(
+            summary,
+            Summary {
+                passed: 1,
+                flaky: 0,
+                failed: 1,
+                errors: 1,
+                skipped: 1,
+                xfailed: 1,
+                duration: 1.5,
+            }
+        );
+        assert_eq!(
+            summary.to_string(),
+            "1 passed, 1 failed, 1 error, 1 skipped, 1 xfailed in 1.50s"
+        );
+
+        let slowest: Vec<String> = report.slowest(2).iter().map(|x| x.id()).collect();
+        assert_eq!(
+            slowest,
+            vec![
+                "tests.test_foo.TestFoo::test_fail[1]",
+                "tests.test_bar::test_teardown"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_retry() {
+        let mut report = TestReport::parse(REPORT).unwrap();
+        assert_eq!(
+            report.failing(),
+            vec![
+                "tests.test_foo.TestFoo::test_fail[1]",
+                "tests.test_bar::test_teardown"
+            ]
+        );
+
+        // Pytest reruns only the failed tests, one of which passes this time.
+        let retry = TestReport::parse(
+            r#"<testsuites><testsuite time="0.5">
+                <testcase classname="tests.test_foo.TestFoo" name="test_fail[1]" time="0.250" />
+                <testcase classname="tests.test_bar" name="test_teardown" time="0.250">
+                  <error message="failed on teardown" />
+                </testcase>
+            </testsuite></testsuites>"#,
+        )
+        .unwrap();
+        report.merge_retry(&retry);
+        assert_eq!(report.tests[1].outcome, TestOutcome::Flaky);
+        assert_eq!(report.tests[4].outcome, TestOutcome::Error);
+        assert_eq!(report.failing(), vec!["tests.test_bar::test_teardown"]);
+        assert_eq!(report.flaky(), vec!["tests.test_foo.TestFoo::test_fail[1]"]);
+        assert_eq!(
+            report.summary().to_string(),
+            "1 passed, 1 flaky, 1 error, 1 skipped, 1 xfailed in 2.00s"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use tire::lint::{fixed_rules, split_diff};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::diagnostic;
+
+    #[test]
+    fn test_split_diff() {
+        let diff = "--- src/a.py
++++ src/a.py
+@@ -1,2 +1 @@
+-import os
+ import sys
+
+--- src/b.py
++++ src/b.py
+@@ -1 +1 @@
+--- x
++x
+";
+        assert_eq!(
+            split_diff(diff),
+            vec![
+                (
+                    "src/a.py".to_owned(),
+                    "--- src/a.py\n+++ src/a.py\n@@ -1,2 +1 @@\n-import os\n import sys\n"
+                        .to_owned()
+                ),
+                (
+                    "src/b.py".to_owned(),
+                    "--- src/b.py\n+++ src/b.py\n@@ -1 +1 @@\n--- x\n+x\n".to_owned()
+                ),
+            ]
+        );
+        assert!(split_diff("").is_empty());
+    }
+
+    #[test]
+    fn test_fixed_rules() {
+        let before = [
+            diagnostic(1, "F401"),
+            diagnostic(1, "F401"),
+            diagnostic(1, "I001"),
+            diagnostic(1, "E501"),
+        ];
+        let after = [diagnostic(1, "F401"), diagnostic(1, "E501")];
+        let fixed: Vec<(String, usize)> = fixed_rules(&before, &after).into_iter().collect();
+        assert_eq!(fixed, vec![("F401".to_owned(), 1), ("I001".to_owned(), 1)]);
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs;
-use tire::profile::Profile;
+use tire::profile::{Profile, configured_profile};
 use toml::Value;
 use toml::value::Table;
 
@@ -68,4 +68,20 @@ mod tests {
             Value::String("${TIRE_MIN_PYTHON_VERSION}".to_string())
         );
     }
+
+    #[test]
+    fn test_configured_profile() {
+        let pyproject: Table = r#"
+            [tool.tire]
+            profile = "https://example.com/profile.toml"
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            configured_profile(&pyproject),
+            Some("https://example.com/profile.toml")
+        );
+        assert_eq!(configured_profile(&Table::new()), None);
+        assert!(Profile::load(Some("not-a-url".to_owned())).is_err());
+    }
 }
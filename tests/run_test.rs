@@ -0,0 +1,272 @@
+use std::process::Command;
+use tire::{
+    profile::Profile,
+    run::{
+        Adapter, RunArgs, Target, UV_RUN_VALUE_OPTIONS, bootstrap, configured_adapter, split_args,
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_adapter() {
+        assert_eq!("cyclopts".parse(), Ok(Adapter::Cyclopts));
+        assert_eq!(
+            "argparse-from-signature".parse(),
+            Ok(Adapter::ArgparseFromSignature)
+        );
+        assert_eq!("none".parse(), Ok(Adapter::None));
+        assert!("click".parse::<Adapter>().is_err());
+        assert_eq!(Adapter::None.requirement(), None);
+        assert_eq!(Adapter::Typer.requirement(), Some("typer"));
+    }
+
+    #[test]
+    fn test_configured_adapter() {
+        let dir = std::env::temp_dir().join(format!("tire-adapter-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let profile = Profile::load_string(
+            "test".to_owned(),
+            "[tool.tire.run]\nadapter = \"typer\"\n".to_owned(),
+        )
+        .unwrap();
+
+        // The profile's adapter applies, unless the project configures another one.
+        assert_eq!(
+            configured_adapter(&profile, Some(dir.clone())),
+            Adapter::Typer
+        );
+        std::fs::write(
+            dir.join("pyproject.toml"),
+            "[tool.tire.run]\nadapter = \"none\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            configured_adapter(&profile, Some(dir.clone())),
+            Adapter::None
+        );
+        assert!(!dir.join(".tire").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            "pkg.sub:func".parse(),
+            Ok(Target {
+                module: "pkg.sub".to_owned(),
+                attributes: "func".to_owned(),
+            })
+        );
+        let target: Target = "pkg.mod:Class.method".parse().unwrap();
+        assert_eq!(target.module, "pkg.mod");
+        assert_eq!(target.attributes, "Class.method");
+        assert_eq!(target.to_string(), "pkg.mod:Class.method");
+        assert!("_private:main_2".parse::<Target>().is_ok());
+    }
+
+    #[test]
+    fn test_parse_malformed_target() {
+        for target in [
+            "a:b;import os",
+            "a:b()",
+            "a:b c",
+            ":func",
+            "mod:",
+            "mod:func:other",
+            "pkg..mod:func",
+            "pkg.:func",
+            "1mod:func",
+            "mod:'func'",
+        ] {
+            assert!(target.parse::<Target>().is_err(), "{target}");
+        }
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        // The bootstrap script is fixed and reads the target from its arguments.
+        let code = bootstrap(Adapter::Fire);
+        assert!(code.contains("importlib.import_module(module)"));
+        assert!(code.contains("sys.argv[0].partition(\":\")"));
+        assert!(code.ends_with("fire.Fire(target, name=sys.argv[0])\n"));
+    }
+
+    fn split(args: &[&str]) -> Option<RunArgs> {
+        split_args(args.iter().map(|x| x.to_string()).collect())
+    }
+
+    fn run_args(uv_args: &[&str], target: &str, target_args: &[&str]) -> Option<RunArgs> {
+        Some(RunArgs {
+            uv_args: uv_args.iter().map(|x| x.to_string()).collect(),
+            target: target.to_owned(),
+            target_args: target_args.iter().map(|x| x.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn test_split_args_with_values() {
+        assert_eq!(
+            split(&["--with", "requests", "script.py", "-v"]),
+            run_args(&["--with", "requests"], "script.py", &["-v"])
+        );
+        assert_eq!(
+            split(&[
+                "-p",
+                "3.12",
+                "--frozen",
+                "--env-file",
+                ".env",
+                "mod:main",
+                "x"
+            ]),
+            run_args(
+                &["-p", "3.12", "--frozen", "--env-file", ".env"],
+                "mod:main",
+                &["x"]
+            )
+        );
+        assert_eq!(
+            split(&["--python=3.12", "-m", "http.server"]),
+            run_args(&["--python=3.12", "-m"], "http.server", &[])
+        );
+    }
+
+    #[test]
+    fn test_split_args_separator() {
+        assert_eq!(
+            split(&["--with", "x", "--", "-script.py", "--", "y"]),
+            run_args(&["--with", "x"], "-script.py", &["--", "y"])
+        );
+        assert_eq!(
+            split(&["script.py", "--", "--with"]),
+            run_args(&[], "script.py", &["--", "--with"])
+        );
+    }
+
+    #[test]
+    fn test_split_args_missing_target() {
+        assert_eq!(split(&[]), None);
+        assert_eq!(split(&["--frozen"]), None);
+        assert_eq!(split(&["--with", "requests"]), None);
+        assert_eq!(split(&["--with"]), None);
+        assert_eq!(split(&["--"]), None);
+    }
+
+    /// Returns the options that take a value from the output of `uv run --help`, where they are
+    /// listed as e.g. `  -p, --python <PYTHON>`.
+    fn value_options(help: &str) -> Vec<String> {
+        let mut options: Vec<String> = help
+            .lines()
+            .map(str::trim_start)
+            .filter(|x| x.starts_with('-'))
+            .filter_map(|x| x.split_once(" <").map(|(names, _)| names))
+            .flat_map(|names| names.split(", "))
+            .filter(|x| x.len() > 1 && !x.contains(' '))
+            .map(String::from)
+            .collect();
+        options.sort();
+        options.dedup();
+        options
+    }
+
+    #[test]
+    fn test_value_options() {
+        let help = "
+Options:
+  -m, --module
+          Run a Python module
+      --with <WITH>
+          Run with the given packages installed
+  -p, --python <PYTHON>
+          The Python interpreter to use for the run environment.
+      --color <COLOR_CHOICE>
+          Control the use of color in output [possible values: auto, always, never]
+
+          - auto: Enables colored output only when the output is going to a terminal
+";
+        assert_eq!(
+            value_options(help),
+            vec!["--color", "--python", "--with", "-p"]
+        );
+    }
+
+    #[test]
+    fn test_bootstrap_argparse_from_signature() {
+        // Skip the test if Python isn't installed.
+        if Command::new("python3").arg("--version").output().is_err() {
+            return;
+        }
+
+        let dir = std::env::temp_dir().join(format!("tire-bootstrap-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("pkg/sub")).unwrap();
+        std::fs::write(dir.join("pkg/__init__.py"), "").unwrap();
+        std::fs::write(dir.join("pkg/sub/__init__.py"), "").unwrap();
+        std::fs::write(
+            dir.join("pkg/sub/mod.py"),
+            r#"import asyncio
+
+
+def add(a: int, b: int = 2, *, verbose: bool = False) -> str:
+    return f"{a} + {b} = {a + b}" if verbose else str(a + b)
+
+
+class Greeter:
+    @classmethod
+    def greet(cls, name: str, punctuation: str = "!") -> str:
+        return f"Hello, {name}{punctuation}"
+
+
+async def wait(*names: str) -> str:
+    await asyncio.sleep(0)
+    return ", ".join(names)
+"#,
+        )
+        .unwrap();
+
+        let run = |args: &[&str]| {
+            let output = Command::new("python3")
+                .arg("-c")
+                .arg(bootstrap(Adapter::ArgparseFromSignature))
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            String::from_utf8_lossy(&output.stdout).to_string()
+        };
+        assert_eq!(run(&["pkg.sub.mod:add", "1"]), "3\n");
+        assert_eq!(
+            run(&["pkg.sub.mod:add", "1", "--b", "3", "--verbose"]),
+            "1 + 3 = 4\n"
+        );
+        assert_eq!(
+            run(&["pkg.sub.mod:Greeter.greet", "World", "--punctuation", "?"]),
+            "Hello, World?\n"
+        );
+        assert_eq!(run(&["pkg.sub.mod:wait", "a", "b"]), "a, b\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_uv_run_value_options() {
+        // Skip the check if uv isn't installed.
+        let Ok(output) = Command::new("uv").args(["run", "--help"]).output() else {
+            return;
+        };
+        let missing: Vec<String> = value_options(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|x| !UV_RUN_VALUE_OPTIONS.contains(&x.as_str()))
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "options of `uv run` missing from UV_RUN_VALUE_OPTIONS: {missing:?}"
+        );
+    }
+}
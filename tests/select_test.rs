@@ -0,0 +1,170 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use tire::select::{
+    GitSelection, git, git_files, imported_modules, is_test_file, module_names, python_files,
+    relative_to_cwd,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{commit, repo};
+
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file(Path::new("tests/test_foo.py")));
+        assert!(is_test_file(Path::new("foo_test.py")));
+        assert!(!is_test_file(Path::new("src/pkg/testing.py")));
+        assert!(!is_test_file(Path::new("tests/conftest.py")));
+    }
+
+    #[test]
+    fn test_module_names() {
+        assert_eq!(module_names(Path::new("pkg/mod.py")), vec!["pkg.mod"]);
+        assert_eq!(
+            module_names(Path::new("src/pkg/sub/__init__.py")),
+            vec!["src.pkg.sub", "pkg.sub"]
+        );
+        assert!(module_names(Path::new("__init__.py")).is_empty());
+    }
+
+    #[test]
+    fn test_imported_modules() {
+        let source = r#"
+import os, sys as system
+import pkg.sub.mod
+from pkg.util import helper  # comment
+from . import relative
+from pkg.models import (
+    User,
+    Group as G,
+)
+
+def test_foo():
+    from pkg import lazy
+"#;
+        assert_eq!(
+            imported_modules(source),
+            vec![
+                "os",
+                "sys",
+                "pkg.sub.mod",
+                "pkg.util",
+                "pkg.util.helper",
+                "pkg.models",
+                "pkg.models.User",
+                "pkg.models.Group",
+                "pkg",
+                "pkg.lazy",
+            ]
+        );
+    }
+
+    /// Writes *files* relative to *dir*, creating parent directories as needed.
+    fn write(dir: &Path, files: &[&str]) {
+        for file in files {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, format!("# {file}\n")).unwrap();
+        }
+    }
+
+    fn paths(files: &[&str]) -> Vec<PathBuf> {
+        files.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_git_files_changed_and_staged() {
+        let dir = repo("select-changed");
+        write(&dir, &["a.py", "b.py", "c.py"]);
+        std::fs::write(dir.join(".gitignore"), "build/\n").unwrap();
+        commit(&dir, "init");
+
+        // A modified, a deleted, a staged, an untracked and an ignored file.
+        std::fs::write(dir.join("a.py"), "x = 1\n").unwrap();
+        std::fs::remove_file(dir.join("b.py")).unwrap();
+        write(&dir, &["d.py", "e.py", "build/f.py"]);
+        git(&dir, &["add", "d.py"]).unwrap();
+
+        assert_eq!(
+            git_files(&dir, &GitSelection::Changed).unwrap(),
+            paths(&["a.py", "d.py", "e.py"])
+        );
+        assert_eq!(
+            git_files(&dir, &GitSelection::Staged).unwrap(),
+            paths(&["d.py"])
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_git_files_since() {
+        let dir = repo("select-since");
+        write(&dir, &["a.py", "b.py"]);
+        commit(&dir, "init");
+        git(&dir, &["checkout", "-q", "-b", "feature"]).unwrap();
+        write(&dir, &["c.py"]);
+        commit(&dir, "feature");
+
+        // Commits on `main` after the merge base are not part of the selection.
+        git(&dir, &["checkout", "-q", "main"]).unwrap();
+        write(&dir, &["main.py"]);
+        commit(&dir, "main");
+        git(&dir, &["checkout", "-q", "feature"]).unwrap();
+
+        std::fs::write(dir.join("a.py"), "x = 1\n").unwrap();
+        write(&dir, &["untracked.py"]);
+        assert_eq!(
+            git_files(&dir, &GitSelection::Since("main".to_owned())).unwrap(),
+            paths(&["a.py", "c.py", "untracked.py"])
+        );
+        assert!(git_files(&dir, &GitSelection::Since("nonexistent".to_owned())).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_python_files_relative_to_cwd() {
+        // The project is a subdirectory of the repository.
+        let dir = repo("select-cwd");
+        write(
+            &dir,
+            &[
+                "root.py",
+                "app/pyproject.toml",
+                "app/pkg/mod.py",
+                "app/tests/test_mod.py",
+            ],
+        );
+        commit(&dir, "init");
+        for file in ["root.py", "app/pkg/mod.py", "app/tests/test_mod.py"] {
+            std::fs::write(dir.join(file), "x = 1\n").unwrap();
+        }
+        write(&dir, &["app/README.md"]);
+
+        let project_root = dir.join("app");
+        assert_eq!(
+            git_files(&project_root, &GitSelection::Changed).unwrap(),
+            paths(&["README.md", "pkg/mod.py", "tests/test_mod.py"])
+        );
+
+        // Paths inside the working directory are relative to it, others are absolute.
+        let cwd = project_root.join("pkg");
+        assert_eq!(
+            relative_to_cwd(&cwd, &project_root, &paths(&["pkg/mod.py"])),
+            vec!["mod.py"]
+        );
+        assert_eq!(
+            python_files(&cwd, &GitSelection::Changed).unwrap(),
+            Some(vec![
+                "mod.py".to_owned(),
+                project_root
+                    .join("tests/test_mod.py")
+                    .to_string_lossy()
+                    .to_string()
+            ])
+        );
+        assert_eq!(python_files(&dir, &GitSelection::Staged).unwrap(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+use tire::shard::{Durations, Shard, Strategy, junit_id, partition, select};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_ids() -> Vec<String> {
+        [
+            "tests/test_a.py::test_1",
+            "tests/test_a.py::test_2",
+            "tests/test_b.py::TestB::test_1",
+            "tests/test_c.py::test_1[x::y]",
+            "tests/test_d.py::test_1",
+        ]
+        .iter()
+        .map(|x| x.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_shard() {
+        assert_eq!(
+            "2/5".parse::<Shard>().unwrap(),
+            Shard { index: 2, count: 5 }
+        );
+        assert!("0/5".parse::<Shard>().is_err());
+        assert!("6/5".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+        assert!("a/b".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn test_junit_id() {
+        assert_eq!(junit_id("tests/test_a.py::test_1"), "tests.test_a::test_1");
+        assert_eq!(
+            junit_id("tests/test_b.py::TestB::test_1"),
+            "tests.test_b.TestB::test_1"
+        );
+        assert_eq!(
+            junit_id("tests/test_c.py::test_1[x::y]"),
+            "tests.test_c::test_1[x::y]"
+        );
+    }
+
+    #[test]
+    fn test_partition() {
+        let items: Vec<(String, f64)> = [("a", 5.0), ("b", 3.0), ("c", 2.0), ("d", 1.0)]
+            .iter()
+            .map(|(x, w)| (x.to_string(), *w))
+            .collect();
+        assert_eq!(
+            partition(&items, 2),
+            vec![
+                vec!["a".to_owned(), "d".to_owned()],
+                vec!["b".to_owned(), "c".to_owned()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_covers_all_files_once() {
+        let node_ids = node_ids();
+        let mut all: Vec<String> = (1..=3)
+            .flat_map(|index| {
+                select(
+                    &node_ids,
+                    Shard { index, count: 3 },
+                    Strategy::File,
+                    &Durations::new(),
+                )
+            })
+            .collect();
+        all.sort();
+        assert_eq!(
+            all,
+            vec![
+                "tests/test_a.py",
+                "tests/test_b.py",
+                "tests/test_c.py",
+                "tests/test_d.py"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_by_duration() {
+        let durations: Durations = [
+            ("tests.test_a::test_1".to_owned(), 0.1),
+            ("tests.test_a::test_2".to_owned(), 0.1),
+            ("tests.test_b.TestB::test_1".to_owned(), 10.0),
+            ("tests.test_c::test_1[x::y]".to_owned(), 0.1),
+        ]
+        .into_iter()
+        .collect();
+        let shard = Shard { index: 1, count: 2 };
+        assert_eq!(
+            select(&node_ids(), shard, Strategy::Duration, &durations),
+            vec!["tests/test_b.py"]
+        );
+        let shard = Shard { index: 2, count: 2 };
+        assert_eq!(
+            select(&node_ids(), shard, Strategy::Duration, &durations),
+            vec!["tests/test_a.py", "tests/test_c.py", "tests/test_d.py"]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::Path;
+use tire::profile::{Profile, external_state_dir, rebase_paths, state_dir};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_state_dir() {
+        let a = external_state_dir(Path::new("/cache/tire"), Path::new("/nonexistent/work/app"));
+        let b = external_state_dir(
+            Path::new("/cache/tire"),
+            Path::new("/nonexistent/other/app"),
+        );
+        assert!(a.starts_with("/cache/tire"));
+        assert!(a.file_name().unwrap().to_string_lossy().starts_with("app-"));
+        assert_ne!(a, b);
+        assert_eq!(
+            a,
+            external_state_dir(Path::new("/cache/tire"), Path::new("/nonexistent/work/app"))
+        );
+    }
+
+    #[test]
+    fn test_rebase_paths() {
+        let mut config: toml::Table = toml::from_str(
+            r#"
+            [tool.mypy]
+            files = "src, tests"
+            mypy_path = ["src", "/abs/stubs"]
+            [tool.ruff]
+            src = ["src"]
+            exclude = ["build"]
+            "#,
+        )
+        .unwrap();
+        rebase_paths(&mut config, Path::new("/project"));
+
+        let mypy_path: Vec<&str> = config["tool"]["mypy"]["mypy_path"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|x| x.as_str())
+            .collect();
+        assert_eq!(mypy_path, vec!["/project/src", "/abs/stubs"]);
+        assert_eq!(
+            config["tool"]["mypy"]["files"].as_str(),
+            Some("/project/src,/project/tests")
+        );
+        assert_eq!(
+            config["tool"]["ruff"]["src"][0].as_str(),
+            Some("/project/src")
+        );
+        assert_eq!(config["tool"]["ruff"]["exclude"][0].as_str(), Some("build"));
+    }
+
+    #[test]
+    fn test_relative_state_dir_outside_project() {
+        let dir = std::env::temp_dir().join(format!("tire-relative-{}", std::process::id()));
+        let project_root = dir.join("app");
+        std::fs::create_dir_all(&project_root).unwrap();
+        std::fs::write(
+            project_root.join("pyproject.toml"),
+            "[tool.tire]\nstate-dir = \"../state\"\n",
+        )
+        .unwrap();
+        let state_dir = state_dir(&project_root);
+        assert!(state_dir.starts_with(dir.join("state")));
+        assert!(!state_dir.starts_with(&project_root));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merged_config_writes_nothing() {
+        let dir = std::env::temp_dir().join(format!("tire-merged-{}", std::process::id()));
+        let project_root = dir.join("app");
+        std::fs::create_dir_all(&project_root).unwrap();
+        std::fs::write(
+            project_root.join("pyproject.toml"),
+            format!(
+                "[tool.tire]\nstate-dir = {:?}\n\n[tool.ruff]\nsrc = [\"src\"]\n",
+                dir.join("state")
+            ),
+        )
+        .unwrap();
+
+        let profile = Profile::load(Some("default".to_owned())).unwrap();
+        let (root, config) = profile.merged_config(Some(project_root.clone())).unwrap();
+        assert_eq!(root, project_root);
+        assert!(!dir.join("state").exists());
+
+        // The configuration is the same as the materialized one, including rebased paths.
+        let materialized = profile.materialize(Some(project_root.clone())).unwrap();
+        assert!(materialized.file.starts_with(state_dir(&project_root)));
+        assert_eq!(
+            std::fs::read_to_string(&materialized.file).unwrap(),
+            toml::to_string(&config).unwrap()
+        );
+        assert!(
+            config["tool"]["ruff"]["src"][0]
+                .as_str()
+                .unwrap()
+                .starts_with(project_root.to_str().unwrap())
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use tire::watch::Filter;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::repo;
+
+    #[test]
+    fn test_is_relevant() {
+        // The project is a subdirectory of the repository.
+        let dir = repo("watch-relevant");
+        let project_root = dir.join("app");
+        for subdir in ["pkg/sub", "build", "generated", ".venv/lib", ".tire"] {
+            std::fs::create_dir_all(project_root.join(subdir)).unwrap();
+        }
+        std::fs::write(dir.join(".gitignore"), "build/\n").unwrap();
+        std::fs::write(project_root.join("pkg/.gitignore"), "*_pb2.py\n").unwrap();
+        std::fs::write(dir.join(".git/info/exclude"), "generated/\n").unwrap();
+
+        let filter = Filter::new(&project_root);
+        assert!(filter.is_relevant(&project_root.join("pyproject.toml")));
+        assert!(filter.is_relevant(&project_root.join("pkg/mod.py")));
+        assert!(filter.is_relevant(&project_root.join("pkg/sub/mod.pyi")));
+        assert!(!filter.is_relevant(&project_root.join("README.md")));
+        assert!(!filter.is_relevant(&project_root.join("pkg/sub/pyproject.toml")));
+
+        // Ignored by the repository's `.gitignore`, a nested `.gitignore` and `.git/info/exclude`.
+        assert!(!filter.is_relevant(&project_root.join("build/mod.py")));
+        assert!(!filter.is_relevant(&project_root.join("pkg/sub/api_pb2.py")));
+        assert!(!filter.is_relevant(&project_root.join("generated/mod.py")));
+
+        // Hidden directories, the state directory and files outside the project.
+        assert!(!filter.is_relevant(&project_root.join(".venv/lib/mod.py")));
+        assert!(!filter.is_relevant(&project_root.join(".tire/mod.py")));
+        assert!(!filter.is_relevant(&dir.join("mod.py")));
+
+        let directories = filter.directories(&project_root);
+        assert_eq!(
+            directories,
+            vec![
+                project_root.clone(),
+                project_root.join("pkg"),
+                project_root.join("pkg/sub"),
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
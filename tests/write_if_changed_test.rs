@@ -0,0 +1,22 @@
+use tire::utils::write_if_changed;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_if_changed() {
+        let dir = std::env::temp_dir().join(format!("tire-write-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("pyproject.toml");
+
+        assert!(write_if_changed(&file, "a = 1\n").unwrap());
+        assert!(!write_if_changed(&file, "a = 1\n").unwrap());
+        assert!(write_if_changed(&file, "a = 2\n").unwrap());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "a = 2\n");
+
+        // No temporary files are left behind.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}